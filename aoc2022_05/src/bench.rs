@@ -0,0 +1,104 @@
+use std::{collections::VecDeque, time::Instant};
+
+use crate::{operate_crane, take_crates, top_crates, Instruction, ShipStacks};
+
+/// Times the bulk slice-transfer crane against the original crate-at-a-time
+/// `VecDeque` crane on a generated ship.
+///
+/// Usage: `bench [stacks] [crates] [moves]`
+pub fn run<I: Iterator<Item = String>>(mut args: I) {
+    let stack_count: usize = args.next().map_or(9, |s| s.parse().unwrap());
+    let crate_count: usize = args.next().map_or(100_000, |s| s.parse().unwrap());
+    let move_count: usize = args.next().map_or(10_000, |s| s.parse().unwrap());
+
+    let (stacks, instructions) = generate(stack_count, crate_count, move_count);
+    println!("{stack_count} stacks, {crate_count} crates, {move_count} moves");
+
+    let start = Instant::now();
+    let legacy = legacy_crane(&stacks, &instructions);
+    println!("VecDeque one at a time: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let bulk = operate_crane(stacks, &instructions, |stacks, instruction| {
+        let crates = take_crates(stacks, instruction);
+        stacks[instruction.dst - 1].extend(crates);
+    });
+    println!("Vec split_off/extend:   {:?}", start.elapsed());
+
+    assert_eq!(top_crates(&bulk), legacy);
+}
+
+/// The original part 2 crane, moving crates one at a time through a
+/// temporary deque.
+fn legacy_crane(stacks: &ShipStacks, instructions: &[Instruction]) -> String {
+    let mut stacks: Vec<VecDeque<char>> = stacks
+        .iter()
+        .map(|stack| stack.iter().copied().collect())
+        .collect();
+
+    for instruction in instructions {
+        let mut tmp: VecDeque<char> = VecDeque::default();
+        for _ in 0..instruction.count {
+            tmp.push_front(stacks[instruction.src - 1].pop_back().unwrap());
+        }
+        for _ in 0..instruction.count {
+            stacks[instruction.dst - 1].push_back(tmp.pop_front().unwrap());
+        }
+    }
+
+    stacks.iter().map(|stack| stack.back().unwrap()).collect()
+}
+
+fn generate(
+    stack_count: usize,
+    crate_count: usize,
+    move_count: usize,
+) -> (ShipStacks, Vec<Instruction>) {
+    assert!(stack_count >= 2, "need at least two stacks to move crates");
+    // With more crates than stacks some stack always has a crate to spare,
+    // so a legal move can always be found.
+    assert!(
+        crate_count > stack_count,
+        "need more crates than stacks to keep every stack occupied"
+    );
+    let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+
+    let mut stacks: ShipStacks = vec![vec![]; stack_count];
+    for i in 0..crate_count {
+        let cr8 = (b'A' + (rng.next() % 26) as u8) as char;
+        stacks[i % stack_count].push(cr8);
+    }
+
+    // Track heights so every move is legal, and never empty a stack so the
+    // top crates can always be read off at the end.
+    let mut heights: Vec<usize> = stacks.iter().map(|stack| stack.len()).collect();
+    let mut instructions = vec![];
+    while instructions.len() < move_count {
+        let src = rng.next() as usize % stack_count;
+        let dst = rng.next() as usize % stack_count;
+        if src == dst || heights[src] < 2 {
+            continue;
+        }
+        let count = 1 + rng.next() as usize % (heights[src] - 1);
+        heights[src] -= count;
+        heights[dst] += count;
+        instructions.push(Instruction {
+            count,
+            src: src + 1,
+            dst: dst + 1,
+        });
+    }
+
+    (stacks, instructions)
+}
+
+struct XorShift(u64);
+
+impl XorShift {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
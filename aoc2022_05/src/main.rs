@@ -1,61 +1,137 @@
+mod bench;
+mod render;
+
 const INPUT: &str = include_str!("real_input.txt");
 
 type ShipStacks = Vec<CargoStack>;
-type CargoStack = std::collections::VecDeque<char>;
+// Top of the stack is the end of the Vec, so a multi-crate move is a single
+// `split_off` from the source followed by an `extend` onto the destination.
+type CargoStack = Vec<char>;
 
 fn main() {
-    // part 1
-    {
-        let part1 = operate_crane(|stacks, instruction| {
-            for _ in 0..instruction.count {
-                let cr8 = stacks[instruction.src - 1].pop_back().unwrap();
-                stacks[instruction.dst - 1].push_back(cr8);
+    let mut args = std::env::args().skip(1);
+    let mut format = OutputFormat::Top;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "bench" => {
+                bench::run(args);
+                return;
             }
-        });
-        dbg!(part1);
+            "--format" => format = args.next().unwrap().parse().unwrap(),
+            _ => panic!("Unknown argument {arg}"),
+        }
     }
 
+    let (stacks, instructions) = parse(INPUT);
+
+    // part 1
+    let part1 = operate_crane(stacks.clone(), &instructions, |stacks, instruction| {
+        let crates = take_crates(stacks, instruction);
+        stacks[instruction.dst - 1].extend(crates.into_iter().rev());
+    });
+
     // part 2
-    {
-        let part2 = operate_crane(|stacks, instruction| {
-            let mut tmp: CargoStack = CargoStack::default();
-            for _ in 0..instruction.count {
-                tmp.push_front(stacks[instruction.src - 1].pop_back().unwrap());
-            }
-            for _ in 0..instruction.count {
-                stacks[instruction.dst - 1].push_back(tmp.pop_front().unwrap());
-            }
-        });
-        dbg!(part2);
+    let part2 = operate_crane(stacks, &instructions, |stacks, instruction| {
+        let crates = take_crates(stacks, instruction);
+        stacks[instruction.dst - 1].extend(crates);
+    });
+
+    report(&part1, &part2, format);
+}
+
+#[derive(Debug, Clone, Copy)]
+enum OutputFormat {
+    /// The top crate of each stack, the puzzle answer.
+    Top,
+    /// The whole arrangement drawn like the puzzle input.
+    Diagram,
+    /// Every stack as a JSON list, bottom crate first.
+    Json,
+}
+
+impl std::str::FromStr for OutputFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top" => Ok(Self::Top),
+            "diagram" => Ok(Self::Diagram),
+            "json" => Ok(Self::Json),
+            _ => Err(()),
+        }
     }
 }
 
-fn operate_crane<F: Fn(&mut ShipStacks, &Instruction)>(f: F) -> String {
-    let mut lines = INPUT.lines();
+fn report(part1: &ShipStacks, part2: &ShipStacks, format: OutputFormat) {
+    match format {
+        OutputFormat::Top => {
+            let (part1, part2) = (top_crates(part1), top_crates(part2));
+            dbg!(part1, part2);
+        }
+        OutputFormat::Diagram => print!(
+            "part1\n{}part2\n{}",
+            render::diagram(part1),
+            render::diagram(part2)
+        ),
+        OutputFormat::Json => println!(
+            "{{\"part1\":{},\"part2\":{}}}",
+            render::json(part1),
+            render::json(part2)
+        ),
+    }
+}
+
+fn parse(input: &str) -> (ShipStacks, Vec<Instruction>) {
+    let mut lines = input.lines();
 
     let mut line = lines.next().unwrap();
     let mut stacks: ShipStacks = vec![CargoStack::default(); ((line.len() - 3) / 4) + 1];
-    loop {
-        if !line.contains("[") {
-            break;
-        }
+    let mut rows: Vec<&str> = vec![];
+    while line.contains('[') {
+        rows.push(line);
+        line = lines.next().unwrap();
+    }
+    lines.next().unwrap();
 
-        for i in 0..stacks.len() {
-            let cr8 = line.as_bytes()[1 + (4 * i)] as char;
+    // The diagram lists the top crate first, so build each stack bottom up.
+    for row in rows.iter().rev() {
+        for (i, stack) in stacks.iter_mut().enumerate() {
+            let cr8 = row.as_bytes().get(1 + (4 * i)).map_or(' ', |b| *b as char);
             if cr8 != ' ' {
-                stacks[i].push_front(cr8);
+                stack.push(cr8);
             }
         }
-
-        line = lines.next().unwrap();
     }
-    lines.next().unwrap();
 
-    for line in lines {
-        f(&mut stacks, &line.parse().unwrap());
+    let instructions = lines.map(|line| line.parse().unwrap()).collect();
+
+    (stacks, instructions)
+}
+
+fn operate_crane<F: Fn(&mut ShipStacks, &Instruction)>(
+    mut stacks: ShipStacks,
+    instructions: &[Instruction],
+    f: F,
+) -> ShipStacks {
+    for instruction in instructions {
+        f(&mut stacks, instruction);
     }
+    stacks
+}
+
+/// Removes the top `instruction.count` crates from the source stack in one
+/// slice transfer, returned bottom to top.
+fn take_crates(stacks: &mut ShipStacks, instruction: &Instruction) -> CargoStack {
+    let src = &mut stacks[instruction.src - 1];
+    src.split_off(src.len() - instruction.count)
+}
 
-    stacks.iter().map(|stack| stack.back().unwrap()).collect()
+/// The top crate of every stack, with a space standing in for an empty one.
+fn top_crates(stacks: &ShipStacks) -> String {
+    stacks
+        .iter()
+        .map(|stack| stack.last().copied().unwrap_or(' '))
+        .collect()
 }
 
 #[derive(Debug)]
@@ -69,7 +145,11 @@ impl std::str::FromStr for Instruction {
     type Err = ();
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let (count_str, rest) = s[5..].split_once(" from ").ok_or(())?;
+        let (count_str, rest) = s
+            .strip_prefix("move ")
+            .ok_or(())?
+            .split_once(" from ")
+            .ok_or(())?;
         let (src_str, dst_str) = rest.split_once(" to ").ok_or(())?;
         Ok(Self {
             count: count_str.parse().map_err(|_| ())?,
@@ -0,0 +1,47 @@
+use crate::ShipStacks;
+
+/// Renders the stacks in the same layout as the puzzle input, top crate
+/// first, with a numbered label row. Empty stacks keep a blank column.
+pub fn diagram(stacks: &ShipStacks) -> String {
+    let height = stacks.iter().map(|stack| stack.len()).max().unwrap_or(0);
+
+    let mut out = String::new();
+    for level in (0..height).rev() {
+        let row: Vec<String> = stacks
+            .iter()
+            .map(|stack| match stack.get(level) {
+                Some(cr8) => format!("[{cr8}]"),
+                None => "   ".to_string(),
+            })
+            .collect();
+        out.push_str(&row.join(" "));
+        out.push('\n');
+    }
+
+    let labels: Vec<String> = (1..=stacks.len()).map(|i| format!("{i:^3}")).collect();
+    out.push_str(&labels.join(" "));
+    out.push('\n');
+    out
+}
+
+/// Renders each stack as a JSON list of crates from bottom to top, so an
+/// empty stack is `[]`.
+pub fn json(stacks: &ShipStacks) -> String {
+    let lists: Vec<String> = stacks
+        .iter()
+        .map(|stack| {
+            let crates: Vec<String> = stack.iter().map(|cr8| json_char(*cr8)).collect();
+            format!("[{}]", crates.join(","))
+        })
+        .collect();
+    format!("[{}]", lists.join(","))
+}
+
+fn json_char(c: char) -> String {
+    match c {
+        '"' => "\"\\\"\"".to_string(),
+        '\\' => "\"\\\\\"".to_string(),
+        c if c.is_control() => format!("\"\\u{:04x}\"", c as u32),
+        c => format!("\"{c}\""),
+    }
+}
@@ -0,0 +1,83 @@
+use crate::DistinctRun;
+
+/// A message framed by the datastream, starting right after its marker.
+#[derive(Debug)]
+pub struct Frame<'a> {
+    /// Offset of the first byte of the marker that opened this message.
+    pub marker_offset: usize,
+    /// Offset of the first payload byte.
+    pub offset: usize,
+    pub payload: &'a [u8],
+}
+
+/// Splits a datastream into messages. Each message begins after a fresh run
+/// of `marker_len` distinct bytes and ends where the next such marker starts,
+/// or at the end of the stream.
+pub struct Frames<'a> {
+    data: &'a [u8],
+    marker_len: usize,
+    /// Marker and payload offsets of the next message, if there is one.
+    next: Option<(usize, usize)>,
+}
+
+impl<'a> Frames<'a> {
+    pub fn new(data: &'a [u8], marker_len: usize) -> Self {
+        let next = find_marker(data, 0, marker_len).map(|end| (end - marker_len, end));
+        Self {
+            data,
+            marker_len,
+            next,
+        }
+    }
+}
+
+impl<'a> Iterator for Frames<'a> {
+    type Item = Frame<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (marker_offset, offset) = self.next.take()?;
+
+        let end = match find_marker(self.data, offset, self.marker_len) {
+            Some(marker_end) => {
+                let next_marker = marker_end - self.marker_len;
+                self.next = Some((next_marker, marker_end));
+                next_marker
+            }
+            None => self.data.len(),
+        };
+
+        Some(Frame {
+            marker_offset,
+            offset,
+            payload: &self.data[offset..end],
+        })
+    }
+}
+
+/// Returns the offset just past the first distinct run of `marker_len` bytes
+/// starting no earlier than `from`.
+fn find_marker(data: &[u8], from: usize, marker_len: usize) -> Option<usize> {
+    if marker_len == 0 {
+        // An empty marker would frame an endless run of empty messages.
+        return None;
+    }
+
+    let mut run = DistinctRun::default();
+    data[from..]
+        .iter()
+        .position(|b| run.push(*b) >= marker_len)
+        .map(|idx| from + idx + 1)
+}
+
+/// Prints one line per frame with its offsets and escaped payload.
+pub fn dump(frames: Frames) {
+    for (idx, frame) in frames.enumerate() {
+        println!(
+            "#{idx} marker@{} payload@{} len={} {:?}",
+            frame.marker_offset,
+            frame.offset,
+            frame.payload.len(),
+            frame.payload.escape_ascii().to_string()
+        );
+    }
+}
@@ -1,46 +1,195 @@
-use std::collections::VecDeque;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader},
+    ops::ControlFlow,
+};
+
+mod decoder;
 
 const INPUT: &str = include_str!("real_input.txt");
 
-fn main() {
-    // part 1
-    {
-        for line in INPUT.lines() {
-            let part1_start = detect_message_start(line, 4);
-            dbg!(part1_start);
+fn main() -> io::Result<()> {
+    let mut path = None;
+    let mut windows = vec![];
+    let mut all = false;
+    let mut decode = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--window" => windows.push(args.next().unwrap().parse().unwrap()),
+            "--all" => all = true,
+            "--decode" => decode = Some(args.next().unwrap().parse().unwrap()),
+            _ => path = Some(arg),
         }
     }
+    if windows.is_empty() {
+        // part 1 and part 2
+        windows = vec![4, 14];
+    }
 
-    // part 2
-    {
-        for line in INPUT.lines() {
-            let part2_start = detect_message_start(line, 14);
-            dbg!(part2_start);
+    // Decoding frames the whole datastream, so it has to be read up front.
+    if let Some(marker_len) = decode {
+        let data = match path {
+            Some(path) => std::fs::read(path)?,
+            None => INPUT.as_bytes().to_vec(),
+        };
+        for line in data.split(|b| *b == b'\n').filter(|line| !line.is_empty()) {
+            decoder::dump(decoder::Frames::new(line, marker_len));
         }
+        return Ok(());
+    }
+
+    // An optional path streams the datastreams from disk instead, a line
+    // at a time.
+    let mut reader: Box<dyn BufRead> = match path {
+        Some(path) => Box::new(BufReader::new(File::open(path)?)),
+        None => Box::new(INPUT.as_bytes()),
+    };
+    while let Some(reports) = scan_markers(&mut reader, &windows, all)? {
+        print_reports(&reports);
     }
+
+    Ok(())
 }
 
-fn detect_message_start(s: &str, prefix_len: usize) -> usize {
-    let mut window: VecDeque<u8> = Default::default();
+fn print_reports(reports: &[MarkerReport]) {
+    for report in reports {
+        let (window, first) = (report.window, report.first);
+        dbg!(window, first);
+        if let Some(ends) = &report.ends {
+            let markers = ends.len();
+            dbg!(markers);
+            println!("{ends:?}");
+        }
+    }
+}
+
+#[derive(Debug)]
+struct MarkerReport {
+    window: usize,
+    /// Bytes read up to the end of the first distinct window.
+    first: Option<usize>,
+    /// Every position at which a distinct window ends, when requested.
+    ends: Option<Vec<usize>>,
+}
+
+/// Finds the markers for every window size in a single pass over the next
+/// line of the stream, or `None` once the stream has ended.
+///
+/// Without `all` the scan stops as soon as each window size has been seen,
+/// and the rest of the line is skipped.
+fn scan_markers<R: BufRead>(
+    reader: &mut R,
+    windows: &[usize],
+    all: bool,
+) -> io::Result<Option<Vec<MarkerReport>>> {
+    let mut reports: Vec<MarkerReport> = windows
+        .iter()
+        .map(|window| MarkerReport {
+            window: *window,
+            first: (*window == 0).then_some(0),
+            ends: all.then(Vec::new),
+        })
+        .collect();
 
-    for (idx, b) in s.as_bytes().iter().enumerate() {
-        let repeated_idx: Option<usize> = window
-            .iter()
-            .enumerate()
-            .find(|(_, wb)| **wb == *b)
-            .map(|(idx, _)| idx);
-        if let Some(idx) = repeated_idx {
-            for _ in 0..idx + 1 {
-                window.pop_front();
+    let mut run = DistinctRun::default();
+    let more = for_each_byte_of_line(reader, |b| {
+        // Like `lines()`, a `\r\n` line break doesn't count as data.
+        if b == b'\r' {
+            return ControlFlow::Break(());
+        }
+        let len = run.push(b);
+        let mut done = true;
+        for report in reports.iter_mut() {
+            if len >= report.window {
+                report.first.get_or_insert(run.pos);
+                if let Some(ends) = &mut report.ends {
+                    ends.push(run.pos);
+                }
             }
+            done &= report.first.is_some();
+        }
+
+        if done && !all {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
         }
+    })?;
 
-        window.push_back(*b);
+    Ok(more.then_some(reports))
+}
+
+/// Tracks the longest run of distinct bytes ending at the current position.
+///
+/// Each byte's last position is remembered, so the run start only ever moves
+/// forward and the scan is O(n) regardless of window size.
+struct DistinctRun {
+    // Positions are stored one-based so that zero means "not seen yet".
+    last_seen: [usize; 256],
+    start: usize,
+    pos: usize,
+}
 
-        if window.len() == prefix_len {
-            return idx + 1;
+impl Default for DistinctRun {
+    fn default() -> Self {
+        Self {
+            last_seen: [0; 256],
+            start: 0,
+            pos: 0,
         }
     }
+}
+
+impl DistinctRun {
+    /// Adds the next byte and returns the length of the distinct run.
+    fn push(&mut self, b: u8) -> usize {
+        self.pos += 1;
+        let seen = &mut self.last_seen[b as usize];
+        self.start = self.start.max(*seen);
+        *seen = self.pos;
+        self.pos - self.start
+    }
+}
+
+/// Calls `f` with each byte of the next line, without its line break, until
+/// it breaks. The whole line is consumed either way. Returns `false` if the
+/// stream had already ended.
+fn for_each_byte_of_line<R: BufRead, F: FnMut(u8) -> ControlFlow<()>>(
+    reader: &mut R,
+    mut f: F,
+) -> io::Result<bool> {
+    let mut read_any = false;
+    let mut wanted = true;
+    loop {
+        let buf = match reader.fill_buf() {
+            Ok([]) => return Ok(read_any),
+            Ok(buf) => buf,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        read_any = true;
 
-    panic!("Not found");
+        let line_end = buf.iter().position(|b| *b == b'\n');
+        if wanted {
+            for b in &buf[..line_end.unwrap_or(buf.len())] {
+                if f(*b).is_break() {
+                    wanted = false;
+                    break;
+                }
+            }
+        }
+
+        match line_end {
+            Some(end) => {
+                reader.consume(end + 1);
+                return Ok(true);
+            }
+            None => {
+                let len = buf.len();
+                reader.consume(len);
+            }
+        }
+    }
 }
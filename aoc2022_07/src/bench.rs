@@ -0,0 +1,65 @@
+use std::{collections::HashMap, time::Instant};
+
+use crate::{FileSystem, Node, NodeId};
+
+/// Times the bottom-up size aggregation against the original approach of
+/// adding every file's size to each directory on its path, on a generated
+/// chain of nested directories.
+///
+/// Usage: `bench [depth] [files per directory]`
+pub fn run<I: Iterator<Item = String>>(mut args: I) {
+    let depth: usize = args.next().map_or(5_000, |s| s.parse().unwrap());
+    let files: usize = args.next().map_or(10, |s| s.parse().unwrap());
+
+    let mut fs = generate(depth, files);
+    println!("{depth} nested directories, {files} files each");
+
+    let start = Instant::now();
+    let legacy = legacy_dir_sizes(&fs);
+    println!("per-file ancestor walk: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    fs.update_sizes();
+    let dir_sizes = fs.dir_sizes();
+    println!("bottom-up totals:       {:?}", start.elapsed());
+
+    assert_eq!(dir_sizes, legacy);
+}
+
+fn generate(depth: usize, files: usize) -> FileSystem {
+    let mut fs = FileSystem::default();
+    let mut dir = fs.root_id();
+    for level in 0..depth {
+        for file in 0..files {
+            fs.add_file(dir, &format!("f{file}"), level * files + file + 1);
+        }
+        dir = fs.add_dir(dir, &format!("d{level}"));
+    }
+    fs
+}
+
+/// The original part 1 aggregation, cloning the path for every child.
+fn legacy_dir_sizes(fs: &FileSystem) -> HashMap<NodeId, usize> {
+    let mut dir_sizes: HashMap<NodeId, usize> = Default::default();
+    let mut boundary = vec![(fs.root_id(), vec![])];
+
+    while let Some((node_id, path)) = boundary.pop() {
+        match &fs[node_id] {
+            Node::File { size, .. } => {
+                for dir_id in path.iter() {
+                    *dir_sizes.entry(*dir_id).or_default() += size;
+                }
+            }
+            Node::Dir { children, .. } => {
+                dir_sizes.entry(node_id).or_default();
+                for child_id in children.values() {
+                    let mut child_path = path.clone();
+                    child_path.push(node_id);
+                    boundary.push((*child_id, child_path));
+                }
+            }
+        }
+    }
+
+    dir_sizes
+}
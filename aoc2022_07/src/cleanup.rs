@@ -0,0 +1,364 @@
+use std::{collections::HashMap, fmt};
+
+use crate::{report::full_path, FileSystem, Node};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Disk {
+    pub capacity: usize,
+    pub required: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Strategy {
+    /// The smallest directory that frees enough space on its own.
+    SmallestSingle,
+    /// As few directories as possible.
+    FewestDirs,
+    /// The least total space deleted.
+    MinimumBytes,
+}
+
+impl std::str::FromStr for Strategy {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "smallest" => Ok(Self::SmallestSingle),
+            "fewest" => Ok(Self::FewestDirs),
+            "bytes" => Ok(Self::MinimumBytes),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Plan {
+    pub strategy: Strategy,
+    pub disk: Disk,
+    pub used: usize,
+    /// Space that has to be freed to reach `disk.required`.
+    pub needed: usize,
+    /// Full path and size of each directory to delete. None of them are
+    /// nested inside another.
+    pub dirs: Vec<(String, usize)>,
+}
+
+impl Plan {
+    pub fn freed(&self) -> usize {
+        self.dirs.iter().map(|(_, size)| size).sum()
+    }
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let strategy = match self.strategy {
+            Strategy::SmallestSingle => "smallest single directory",
+            Strategy::FewestDirs => "fewest directories",
+            Strategy::MinimumBytes => "minimum bytes deleted",
+        };
+        writeln!(f, "strategy: {strategy}")?;
+        writeln!(
+            f,
+            "{} used of {}, {} required free: {} to free",
+            self.used, self.disk.capacity, self.disk.required, self.needed
+        )?;
+        writeln!(
+            f,
+            "delete {} directories freeing {} ({} over)",
+            self.dirs.len(),
+            self.freed(),
+            self.freed() - self.needed
+        )?;
+        for (path, size) in self.dirs.iter() {
+            writeln!(f, "  {size}  {path}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Most memory the minimum-bytes search may use before giving up.
+const MAX_SEARCH_BYTES: usize = 1 << 30;
+
+#[derive(Debug)]
+pub enum PlanError {
+    /// Even deleting everything isn't enough.
+    NotEnoughSpace,
+    /// The minimum-bytes search would need this many bytes of memory, over
+    /// `MAX_SEARCH_BYTES`.
+    TooLarge(usize),
+}
+
+impl fmt::Display for PlanError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlanError::NotEnoughSpace => write!(f, "no plan frees enough space"),
+            PlanError::TooLarge(bytes) => write!(
+                f,
+                "planning would need {bytes} bytes of memory, over the limit of {MAX_SEARCH_BYTES}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PlanError {}
+
+/// A directory that may be deleted, in depth-first order. Its descendants
+/// are exactly the candidates from its own index up to `end`.
+struct Candidate {
+    path: String,
+    size: usize,
+    end: usize,
+    /// How many candidates it is nested inside.
+    depth: usize,
+}
+
+/// Chooses directories to delete so that at least `disk.required` is free.
+///
+/// Only the smallest single directory may be the root itself; the other
+/// strategies choose among the directories below it.
+pub fn plan(fs: &FileSystem, disk: Disk, strategy: Strategy) -> Result<Plan, PlanError> {
+    let used = fs.total_size(fs.root_id());
+    let needed = disk
+        .required
+        .saturating_sub(disk.capacity.saturating_sub(used));
+
+    let candidates = candidates(fs, matches!(strategy, Strategy::SmallestSingle));
+    let chosen = if needed == 0 {
+        Some(vec![])
+    } else {
+        match strategy {
+            Strategy::SmallestSingle => smallest_single(&candidates, needed),
+            Strategy::FewestDirs => fewest_dirs(&candidates, needed),
+            Strategy::MinimumBytes => {
+                let bytes = minimum_bytes_memory(&candidates, needed);
+                if bytes > MAX_SEARCH_BYTES {
+                    return Err(PlanError::TooLarge(bytes));
+                }
+                minimum_bytes(&candidates, needed)
+            }
+        }
+    };
+    let chosen = chosen.ok_or(PlanError::NotEnoughSpace)?;
+
+    Ok(Plan {
+        strategy,
+        disk,
+        used,
+        needed,
+        dirs: chosen
+            .into_iter()
+            .map(|idx| (candidates[idx].path.clone(), candidates[idx].size))
+            .collect(),
+    })
+}
+
+fn candidates(fs: &FileSystem, include_root: bool) -> Vec<Candidate> {
+    let mut candidates = vec![];
+    let mut path_lens = vec![];
+    fs.visit(|fs, node_id, name, path| {
+        if (include_root || node_id != fs.root_id()) && matches!(fs[node_id], Node::Dir { .. }) {
+            candidates.push(Candidate {
+                path: full_path(name, path),
+                size: fs.total_size(node_id),
+                end: 0,
+                depth: 0,
+            });
+            path_lens.push(path.len());
+        }
+    });
+
+    // A subtree ends at the next candidate that is no deeper than its root.
+    let mut open: Vec<usize> = vec![];
+    for idx in 0..candidates.len() {
+        while let Some(top) = open.pop_if(|top| path_lens[*top] >= path_lens[idx]) {
+            candidates[top].end = idx;
+        }
+        candidates[idx].depth = open.len();
+        open.push(idx);
+    }
+    for top in open {
+        candidates[top].end = candidates.len();
+    }
+
+    candidates
+}
+
+fn smallest_single(candidates: &[Candidate], needed: usize) -> Option<Vec<usize>> {
+    let idx = (0..candidates.len())
+        .filter(|idx| candidates[*idx].size >= needed)
+        .min_by_key(|idx| candidates[*idx].size)?;
+    Some(vec![idx])
+}
+
+/// Deleting a directory frees at least as much as deleting any of the
+/// directories inside it, so once more than one is needed the largest
+/// top-level directories are always the best choice.
+fn fewest_dirs(candidates: &[Candidate], needed: usize) -> Option<Vec<usize>> {
+    // When one directory will do, prefer the one that deletes the least.
+    if let Some(single) = smallest_single(candidates, needed) {
+        return Some(single);
+    }
+
+    let mut top_level = vec![];
+    let mut idx = 0;
+    while idx < candidates.len() {
+        top_level.push(idx);
+        idx = candidates[idx].end;
+    }
+    top_level.sort_by_key(|idx| std::cmp::Reverse(candidates[*idx].size));
+
+    let mut freed = 0;
+    let mut chosen = vec![];
+    for idx in top_level {
+        chosen.push(idx);
+        freed += candidates[idx].size;
+        if freed >= needed {
+            chosen.sort();
+            return Some(chosen);
+        }
+    }
+    None
+}
+
+/// Memory `minimum_bytes` needs: where each total was first reached, plus a
+/// bitset for the walk and one for each enclosing subtree with a jump to it
+/// still pending.
+fn minimum_bytes_memory(candidates: &[Candidate], needed: usize) -> usize {
+    let max_depth = candidates.iter().map(|c| c.depth).max().unwrap_or(0);
+    let bitset = needed.div_ceil(64).saturating_mul(8);
+    needed
+        .saturating_mul(4)
+        .saturating_add(bitset.saturating_mul(max_depth + 2))
+}
+
+/// Walks the candidates in order, either skipping one (moving on to its
+/// first child) or deleting it (jumping past its subtree), tracking every
+/// total below `needed` that is reachable at each position as a bitset.
+/// Finishing means deleting one more directory that takes the total to
+/// `needed` or beyond.
+///
+/// Skipping carries every total forward, so rather than a bitset per
+/// position it's enough to remember where each total was first reached.
+/// Only jumps to positions not walked yet need bitsets of their own.
+fn minimum_bytes(candidates: &[Candidate], needed: usize) -> Option<Vec<usize>> {
+    let n = candidates.len();
+    // first[total] = the first position `total` is reachable at, or u32::MAX
+    let mut first = vec![u32::MAX; needed];
+    let mut current = BitSet::new(needed);
+    let mut jumps: HashMap<usize, BitSet> = HashMap::new();
+    current.set(0);
+    first[0] = 0;
+
+    // (total freed, last deletion, total before it)
+    let mut best: Option<(usize, usize, usize)> = None;
+    for (pos, candidate) in candidates.iter().enumerate() {
+        if let Some(jumped) = jumps.remove(&pos) {
+            current.or_with(&jumped, |total| first[total] = pos as u32);
+        }
+
+        let size = candidate.size;
+        if let Some(before) = current.first_set_from(needed.saturating_sub(size)) {
+            if best.is_none_or(|(total, _, _)| before + size < total) {
+                best = Some((before + size, pos, before));
+            }
+        }
+
+        let end = candidate.end;
+        if size < needed && end < n {
+            jumps
+                .entry(end)
+                .or_insert_with(|| BitSet::new(needed))
+                .or_shifted(&current, size);
+        }
+    }
+
+    let reachable = |total: usize, pos: usize| first[total] as usize <= pos;
+    let (_, last, mut total) = best?;
+    let mut chosen = vec![last];
+    let mut pos = last;
+    while pos > 0 {
+        if reachable(total, pos - 1) {
+            pos -= 1;
+            continue;
+        }
+        let prev = (0..pos)
+            .find(|prev| {
+                candidates[*prev].end == pos
+                    && candidates[*prev].size <= total
+                    && reachable(total - candidates[*prev].size, *prev)
+            })
+            .unwrap();
+        chosen.push(prev);
+        total -= candidates[prev].size;
+        pos = prev;
+    }
+    chosen.reverse();
+    Some(chosen)
+}
+
+#[derive(Debug, Clone)]
+struct BitSet {
+    words: Vec<u64>,
+    len: usize,
+}
+
+impl BitSet {
+    fn new(len: usize) -> Self {
+        Self {
+            words: vec![0; len.div_ceil(64)],
+            len,
+        }
+    }
+
+    fn set(&mut self, idx: usize) {
+        if idx < self.len {
+            self.words[idx / 64] |= 1 << (idx % 64);
+        }
+    }
+
+    /// Sets every bit set in `other`, calling `added` with each one that
+    /// wasn't set already.
+    fn or_with(&mut self, other: &BitSet, mut added: impl FnMut(usize)) {
+        for (idx, (word, other_word)) in self.words.iter_mut().zip(other.words.iter()).enumerate() {
+            let mut new = other_word & !*word;
+            *word |= new;
+            while new != 0 {
+                added(idx * 64 + new.trailing_zeros() as usize);
+                new &= new - 1;
+            }
+        }
+    }
+
+    /// Sets `idx + shift` for every `idx` in `other`, dropping anything that
+    /// falls off the end.
+    fn or_shifted(&mut self, other: &BitSet, shift: usize) {
+        let (word_shift, bit_shift) = (shift / 64, shift % 64);
+        for idx in (word_shift..self.words.len()).rev() {
+            let src = idx - word_shift;
+            let mut word = other.words[src] << bit_shift;
+            if bit_shift > 0 && src > 0 {
+                word |= other.words[src - 1] >> (64 - bit_shift);
+            }
+            self.words[idx] |= word;
+        }
+        if !self.len.is_multiple_of(64) {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1 << (self.len % 64)) - 1;
+        }
+    }
+
+    fn first_set_from(&self, start: usize) -> Option<usize> {
+        if start >= self.len {
+            return None;
+        }
+        let mut idx = start / 64;
+        let mut word = self.words[idx] & (u64::MAX << (start % 64));
+        loop {
+            if word != 0 {
+                return Some(idx * 64 + word.trailing_zeros() as usize);
+            }
+            idx += 1;
+            word = *self.words.get(idx)?;
+        }
+    }
+}
@@ -0,0 +1,192 @@
+use std::{
+    fmt::Write as _,
+    fs::{self, OpenOptions},
+    io::{self, Write},
+    path::Path,
+};
+
+use crate::{report::full_path, FileSystem, Node};
+
+/// Recreates the tree under `target`, with every file a sparse file of its
+/// recorded size. `target` must be empty or not exist yet, and nothing
+/// already on disk is ever overwritten.
+pub fn to_dir(fs: &FileSystem, target: &Path) -> io::Result<()> {
+    check_names(fs)?;
+    fs::create_dir_all(target)?;
+    if fs::read_dir(target)?.next().is_some() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{}: not empty", target.display()),
+        ));
+    }
+
+    let mut result = Ok(());
+    fs.visit(|fs, node_id, name, path| {
+        if result.is_err() || node_id == fs.root_id() {
+            return;
+        }
+        let dest = target.join(full_path(name, path).trim_start_matches('/'));
+        result = match &fs[node_id] {
+            Node::Dir { .. } => fs::create_dir(&dest),
+            Node::File { size, .. } => OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&dest)
+                .and_then(|f| f.set_len(*size as u64)),
+        };
+    });
+    result
+}
+
+/// Writes the tree as a ustar archive. Files are filled with zeros, since
+/// only their sizes are known.
+pub fn to_tar<W: Write>(fs: &FileSystem, mut out: W) -> io::Result<()> {
+    check_names(fs)?;
+    let mut entries = vec![];
+    fs.visit(|fs, node_id, name, path| {
+        // The root is implied by the archive itself.
+        if node_id != fs.root_id() {
+            let path = full_path(name, path).trim_start_matches('/').to_string();
+            match &fs[node_id] {
+                Node::Dir { .. } => entries.push((path + "/", None)),
+                Node::File { size, .. } => entries.push((path, Some(*size))),
+            }
+        }
+    });
+
+    let zeros = [0u8; 512];
+    for (path, size) in entries {
+        out.write_all(&tar_header(&path, size)?)?;
+        let mut remaining = size.unwrap_or(0).next_multiple_of(512);
+        while remaining > 0 {
+            out.write_all(&zeros)?;
+            remaining -= zeros.len();
+        }
+    }
+
+    // The archive ends with two empty blocks.
+    out.write_all(&zeros)?;
+    out.write_all(&zeros)?;
+    out.flush()
+}
+
+/// Fails unless every name is a single path segment, so an export can't
+/// write outside its target or collide with itself.
+fn check_names(fs: &FileSystem) -> io::Result<()> {
+    let mut bad = None;
+    fs.visit(|fs, node_id, name, path| {
+        if bad.is_none()
+            && node_id != fs.root_id()
+            && (name.is_empty() || name == "." || name == ".." || name.contains('/'))
+        {
+            bad = Some(full_path(name, path));
+        }
+    });
+    match bad {
+        Some(path) => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{path}: not a valid name"),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// A ustar header for a directory (`size` of `None`) or a regular file.
+fn tar_header(path: &str, size: Option<usize>) -> io::Result<[u8; 512]> {
+    let invalid = |msg: &str| io::Error::new(io::ErrorKind::InvalidInput, format!("{path}: {msg}"));
+
+    // Paths longer than the name field are split at a `/` into the prefix.
+    let (prefix, name) = if path.len() <= 100 {
+        ("", path)
+    } else {
+        // Searching the bytes keeps a long name from being cut inside a
+        // character; the `/` found is always a character boundary.
+        let split = path.as_bytes()[..path.len().min(156)]
+            .iter()
+            .rposition(|b| *b == b'/')
+            .filter(|split| path.len() - split - 1 <= 100)
+            .ok_or_else(|| invalid("path too long for a tar header"))?;
+        (&path[..split], &path[split + 1..])
+    };
+
+    let (mode, typeflag, size): (&[u8], &[u8], usize) = match size {
+        None => (b"0000755\0", b"5", 0),
+        Some(size) => (b"0000644\0", b"0", size),
+    };
+    if size > 0o77777777777 {
+        return Err(invalid("file too large for a tar header"));
+    }
+
+    let mut header = [0u8; 512];
+    let mut field = |offset: usize, value: &[u8]| {
+        header[offset..offset + value.len()].copy_from_slice(value);
+    };
+    field(0, name.as_bytes());
+    field(100, mode);
+    field(108, b"0000000\0");
+    field(116, b"0000000\0");
+    field(124, format!("{size:011o}\0").as_bytes());
+    field(136, b"00000000000\0");
+    field(148, b"        ");
+    field(156, typeflag);
+    field(257, b"ustar\0");
+    field(263, b"00");
+    field(345, prefix.as_bytes());
+
+    let checksum: u32 = header.iter().map(|b| *b as u32).sum();
+    header[148..156].copy_from_slice(format!("{checksum:06o}\0 ").as_bytes());
+    Ok(header)
+}
+
+/// Produces a `$ cd`/`$ ls` session that reconstructs the directory tree at
+/// `root`. Anything other than regular files and directories is skipped.
+pub fn transcript(root: &Path) -> io::Result<String> {
+    let mut out = String::new();
+    out.push_str("$ cd /\n");
+    transcript_dir(root, &mut out)?;
+    Ok(out)
+}
+
+fn transcript_dir(dir: &Path, out: &mut String) -> io::Result<()> {
+    let mut dirs = vec![];
+    let mut files = vec![];
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().into_string().map_err(|name| {
+            io::Error::new(io::ErrorKind::InvalidData, format!("{name:?}: not UTF-8"))
+        })?;
+        let metadata = fs::symlink_metadata(entry.path())?;
+        if metadata.is_dir() {
+            // Directories are entered with `$ cd`, which splits on whitespace
+            // and treats a leading `-` as a flag. Listed file names are
+            // taken as they are.
+            if name.contains(char::is_whitespace) || name.starts_with('-') {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{name:?}: can't be written to a transcript"),
+                ));
+            }
+            dirs.push(name);
+        } else if metadata.is_file() {
+            files.push((name, metadata.len()));
+        }
+    }
+    dirs.sort();
+    files.sort();
+
+    out.push_str("$ ls\n");
+    for name in dirs.iter() {
+        let _ = writeln!(out, "dir {name}");
+    }
+    for (name, size) in files.iter() {
+        let _ = writeln!(out, "{size} {name}");
+    }
+
+    for name in dirs {
+        let _ = writeln!(out, "$ cd {name}");
+        transcript_dir(&dir.join(&name), out)?;
+        out.push_str("$ cd ..\n");
+    }
+
+    Ok(())
+}
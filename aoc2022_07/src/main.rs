@@ -1,27 +1,87 @@
-use std::{
-    collections::HashMap,
-    ops::{Index, IndexMut},
-};
+use std::{collections::HashMap, ops::Index};
+
+mod bench;
+mod cleanup;
+mod export;
+mod query;
+mod report;
+mod session;
 
 const INPUT: &str = include_str!("real_input.txt");
 
 fn main() {
-    let fs = FileSystem::load();
+    let mut path = None;
+    let mut tree = false;
+    let mut du = None;
+    let mut disk = cleanup::Disk {
+        capacity: 70000000,
+        required: 30000000,
+    };
+    let mut strategy = None;
+    let mut export_dir: Option<String> = None;
+    let mut export_tar: Option<String> = None;
+    let mut queries: Vec<(String, String)> = vec![];
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "bench" => {
+                bench::run(args);
+                return;
+            }
+            "--transcript-from" => {
+                let root = args.next().unwrap();
+                print!("{}", export::transcript(root.as_ref()).unwrap());
+                return;
+            }
+            "--export-dir" => export_dir = args.next(),
+            "--export-tar" => export_tar = args.next(),
+            "--resolve" | "--ls" | "--find" | "--larger-than" => {
+                queries.push((arg, args.next().unwrap()))
+            }
+            "--tree" => tree = true,
+            "--du" => du = Some(args.next().unwrap().parse().unwrap()),
+            "--capacity" => disk.capacity = args.next().unwrap().parse().unwrap(),
+            "--required" => disk.required = args.next().unwrap().parse().unwrap(),
+            "--strategy" => strategy = Some(args.next().unwrap().parse().unwrap()),
+            _ => path = Some(arg),
+        }
+    }
 
-    let mut dir_sizes: HashMap<NodeId, usize> = Default::default();
+    // An optional path replays a transcript read at runtime instead.
+    let transcript = match path {
+        Some(path) => std::fs::read_to_string(path).unwrap(),
+        None => INPUT.to_string(),
+    };
+    let fs = match FileSystem::load(&transcript) {
+        Ok(fs) => fs,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    let dir_sizes = fs.dir_sizes();
+
+    if tree {
+        print!("{}", report::tree(&fs));
+    }
+    if let Some(order) = du {
+        print!("{}", report::du(&fs, order));
+    }
+    for (query, arg) in queries {
+        run_query(&fs, &query, &arg);
+    }
+    if let Some(target) = export_dir {
+        export::to_dir(&fs, target.as_ref()).unwrap();
+    }
+    if let Some(target) = export_tar {
+        let out = std::io::BufWriter::new(std::fs::File::create(target).unwrap());
+        export::to_tar(&fs, out).unwrap();
+    }
 
     // part 1
     {
-        fs.visit(|fs, node_id, path| match &fs[node_id] {
-            Node::File { size } => {
-                for (_, dir_id) in path.iter() {
-                    let current_size = dir_sizes.get(dir_id).map(|v| v.clone()).unwrap_or_default();
-                    dir_sizes.insert(*dir_id, current_size + size);
-                }
-            }
-            _ => {}
-        });
-
         let part1: usize = dir_sizes
             .iter()
             .filter(|(_, size)| **size <= 100000)
@@ -32,16 +92,54 @@ fn main() {
 
     // part 2
     {
-        const TOTAL_SIZE: usize = 70000000;
-        const REQUIRED_SIZE: usize = 30000000;
+        match cleanup::plan(&fs, disk, cleanup::Strategy::SmallestSingle) {
+            Ok(plan) => {
+                let part2 = plan.freed();
+                dbg!(part2);
+            }
+            Err(_) => println!("no single directory frees enough space"),
+        }
+    }
 
-        let free_space = TOTAL_SIZE - *dir_sizes.get(&fs.root_id()).unwrap();
-        let (_, part2) = dir_sizes
-            .iter()
-            .filter(|(_, size)| free_space + **size >= REQUIRED_SIZE)
-            .min_by_key(|(_, size)| Some(*size))
-            .unwrap();
-        dbg!(*part2);
+    if let Some(strategy) = strategy {
+        match cleanup::plan(&fs, disk, strategy) {
+            Ok(plan) => print!("{plan}"),
+            Err(e) => println!("{e}"),
+        }
+    }
+}
+
+fn run_query(fs: &FileSystem, query: &str, arg: &str) {
+    let describe = |node_id| match &fs[node_id] {
+        Node::Dir { .. } => format!("dir, size={}", fs.total_size(node_id)),
+        Node::File { size, .. } => format!("file, size={size}"),
+    };
+
+    match query {
+        "--resolve" => match fs.resolve(arg) {
+            Some(node_id) => println!("{arg} ({})", describe(node_id)),
+            None => println!("{arg}: not found"),
+        },
+        "--ls" => match fs.resolve(arg).map(|node_id| fs.children(node_id)) {
+            Some(Some(children)) => {
+                for (name, child_id) in children {
+                    println!("{name} ({})", describe(child_id));
+                }
+            }
+            Some(None) => println!("{arg}: not a directory"),
+            None => println!("{arg}: not found"),
+        },
+        "--find" => {
+            for (path, node_id) in fs.find(arg) {
+                println!("{path} ({})", describe(node_id));
+            }
+        }
+        "--larger-than" => {
+            for (path, node_id) in fs.files_larger_than(arg.parse().unwrap()) {
+                println!("{path} ({})", describe(node_id));
+            }
+        }
+        _ => unreachable!(),
     }
 }
 
@@ -50,14 +148,19 @@ type NodeId = usize;
 #[derive(Debug)]
 struct FileSystem {
     nodes: Vec<Node>,
+    /// Set by any change to the tree until `update_sizes` runs again.
+    sizes_stale: bool,
 }
 
 impl Default for FileSystem {
     fn default() -> Self {
         Self {
             nodes: vec![Node::Dir {
+                parent: None,
                 children: Default::default(),
+                total_size: 0,
             }],
+            sizes_stale: false,
         }
     }
 }
@@ -70,12 +173,6 @@ impl Index<NodeId> for FileSystem {
     }
 }
 
-impl IndexMut<NodeId> for FileSystem {
-    fn index_mut(&mut self, index: NodeId) -> &mut Self::Output {
-        &mut self.nodes[index]
-    }
-}
-
 impl FileSystem {
     fn root_id(&self) -> NodeId {
         0
@@ -87,88 +184,181 @@ impl FileSystem {
         node_id
     }
 
-    fn visit<F: FnMut(&Self, NodeId, &[(&'static str, NodeId)])>(&self, mut f: F) {
-        let mut boundary = vec![(self.root_id(), "/", vec![])];
+    /// Walks the tree depth first, visiting children in name order. The
+    /// callback gets the node's name and the path of directories above it.
+    fn visit<F: FnMut(&Self, NodeId, &str, &[(&str, NodeId)])>(&self, mut f: F) {
+        // A single path is shared by the whole walk; each boundary entry
+        // remembers how deep it sits so the path can be cut back to it.
+        let mut path: Vec<(&str, NodeId)> = vec![];
+        let mut boundary = vec![(self.root_id(), "/", 0)];
 
-        while let Some((node_id, node_name, path)) = boundary.pop() {
-            f(self, node_id, &path);
-            match &self[node_id] {
-                Node::Dir { children } => {
-                    for (child_name, child_id) in children.iter() {
-                        let mut child_path = path.clone();
-                        child_path.push((node_name, node_id));
-                        boundary.push((*child_id, child_name, child_path))
-                    }
+        while let Some((node_id, node_name, depth)) = boundary.pop() {
+            path.truncate(depth);
+            f(self, node_id, node_name, &path);
+            if let Node::Dir { children, .. } = &self[node_id] {
+                path.push((node_name, node_id));
+                let mut children: Vec<_> = children.iter().collect();
+                children.sort_unstable_by(|(a, _), (b, _)| b.cmp(a));
+                for (child_name, child_id) in children {
+                    boundary.push((*child_id, child_name.as_str(), depth + 1))
                 }
-                _ => {}
             }
         }
     }
 
-    fn load() -> Self {
-        let mut fs = FileSystem::default();
-
-        let mut dir_stack: Vec<usize> = vec![];
-        let mut cwd = fs.root_id();
-
-        for line in INPUT.lines() {
-            if line.starts_with("$ ls") {
-                // ignore
-            } else if line.starts_with("$ cd ") {
-                match &line[5..] {
-                    "/" => {
-                        cwd = fs.root_id();
-                        dir_stack.clear();
-                    }
-                    ".." => {
-                        cwd = dir_stack.pop().unwrap();
-                    }
-                    dir => {
-                        dir_stack.push(cwd);
-                        cwd = fs[cwd].lookup(dir);
-                    }
+    /// Creates an empty directory under `parent` and returns its id.
+    fn add_dir(&mut self, parent: NodeId, name: &str) -> NodeId {
+        let dir = self.insert(Node::Dir {
+            parent: None,
+            children: Default::default(),
+            total_size: 0,
+        });
+        self.attach(parent, name, dir);
+        dir
+    }
+
+    /// Creates a file under `parent` and returns its id.
+    fn add_file(&mut self, parent: NodeId, name: &str, size: usize) -> NodeId {
+        let file = self.insert(Node::File { parent: None, size });
+        self.attach(parent, name, file);
+        file
+    }
+
+    /// Links `node_id` into `parent` as `name`, unlinking whatever was there.
+    fn attach(&mut self, parent: NodeId, name: &str, node_id: NodeId) {
+        self.detach(parent, name);
+        self.nodes[parent].add_child(name, node_id);
+        self.nodes[node_id].set_parent(Some(parent));
+        self.sizes_stale = true;
+    }
+
+    /// Unlinks `name` from `parent`. The node stays in the arena but is no
+    /// longer reachable from the root.
+    fn detach(&mut self, parent: NodeId, name: &str) -> Option<NodeId> {
+        let node_id = self.nodes[parent].remove_child(name)?;
+        self.nodes[node_id].set_parent(None);
+        self.sizes_stale = true;
+        Some(node_id)
+    }
+
+    fn set_file_size(&mut self, node_id: NodeId, new_size: usize) {
+        if let Node::File { size, .. } = &mut self.nodes[node_id] {
+            *size = new_size;
+            self.sizes_stale = true;
+        }
+    }
+
+    /// Recomputes every directory's cached total in a single bottom-up pass.
+    fn update_sizes(&mut self) {
+        let mut order = vec![];
+        let mut boundary = vec![self.root_id()];
+        while let Some(node_id) = boundary.pop() {
+            order.push(node_id);
+            if let Node::Dir {
+                children,
+                total_size,
+                ..
+            } = &mut self.nodes[node_id]
+            {
+                *total_size = 0;
+                boundary.extend(children.values());
+            }
+        }
+
+        // Every child appears after its parent in `order`, so walking it
+        // backwards finishes each directory before adding it to its parent.
+        for node_id in order.into_iter().rev() {
+            let size = self.nodes[node_id].size();
+            if let Some(parent) = self.nodes[node_id].parent() {
+                if let Node::Dir { total_size, .. } = &mut self.nodes[parent] {
+                    *total_size += size;
                 }
-            } else if line.starts_with("dir") {
-                let new_dir = fs.insert(Node::Dir {
-                    children: Default::default(),
-                });
-                fs[cwd].add_child(&line[4..], new_dir);
-            } else {
-                let (size_str, name) = line.split_once(" ").unwrap();
-                let size = size_str.parse().unwrap();
-                let new_file = fs.insert(Node::File { size });
-                fs[cwd].add_child(name, new_file);
             }
         }
 
-        fs
+        self.sizes_stale = false;
+    }
+
+    /// The size of a file, or the cached total of everything under a
+    /// directory.
+    fn total_size(&self, node_id: NodeId) -> usize {
+        debug_assert!(!self.sizes_stale, "update_sizes must run after changes");
+        self[node_id].size()
+    }
+
+    /// The total size of the files under every directory.
+    fn dir_sizes(&self) -> HashMap<NodeId, usize> {
+        let mut dir_sizes: HashMap<NodeId, usize> = Default::default();
+        self.visit(|fs, node_id, _, _| {
+            if let Node::Dir { .. } = &fs[node_id] {
+                dir_sizes.insert(node_id, fs.total_size(node_id));
+            }
+        });
+        dir_sizes
+    }
+
+    /// Rebuilds the filesystem by replaying a shell session transcript.
+    fn load(transcript: &str) -> Result<Self, session::SessionError> {
+        let mut fs = session::Session::replay(transcript)?;
+        fs.update_sizes();
+        Ok(fs)
     }
 }
 
 #[derive(Debug)]
 enum Node {
     Dir {
-        children: HashMap<&'static str, NodeId>,
+        parent: Option<NodeId>,
+        children: HashMap<String, NodeId>,
+        /// Cached by `FileSystem::update_sizes`.
+        total_size: usize,
     },
     File {
+        parent: Option<NodeId>,
         size: usize,
     },
 }
 
 impl Node {
-    fn lookup(&self, name: &str) -> NodeId {
+    fn parent(&self) -> Option<NodeId> {
+        match self {
+            Node::Dir { parent, .. } | Node::File { parent, .. } => *parent,
+        }
+    }
+
+    fn set_parent(&mut self, new_parent: Option<NodeId>) {
         match self {
-            Node::Dir { children } => *children.get(name).unwrap(),
-            _ => panic!("File has no children"),
+            Node::Dir { parent, .. } | Node::File { parent, .. } => *parent = new_parent,
         }
     }
 
-    fn add_child(&mut self, name: &'static str, node_id: NodeId) {
+    fn size(&self) -> usize {
         match self {
-            Node::Dir { children } => {
-                children.insert(name, node_id);
+            Node::Dir { total_size, .. } => *total_size,
+            Node::File { size, .. } => *size,
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<NodeId> {
+        match self {
+            Node::Dir { children, .. } => children.get(name).copied(),
+            Node::File { .. } => None,
+        }
+    }
+
+    fn add_child(&mut self, name: &str, node_id: NodeId) {
+        match self {
+            Node::Dir { children, .. } => {
+                children.insert(name.to_string(), node_id);
             }
             _ => panic!("File can't have children"),
         }
     }
+
+    fn remove_child(&mut self, name: &str) -> Option<NodeId> {
+        match self {
+            Node::Dir { children, .. } => children.remove(name),
+            Node::File { .. } => None,
+        }
+    }
 }
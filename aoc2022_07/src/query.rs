@@ -0,0 +1,89 @@
+use crate::{report::full_path, FileSystem, Node, NodeId};
+
+impl FileSystem {
+    /// Looks up an absolute path such as `/a/b/c.txt`. `.` and `..` are
+    /// followed, with `..` at the root staying at the root.
+    pub fn resolve(&self, path: &str) -> Option<NodeId> {
+        let mut node_id = self.root_id();
+        for segment in path.split('/') {
+            node_id = match segment {
+                "" | "." => node_id,
+                ".." => self[node_id].parent().unwrap_or(node_id),
+                name => self[node_id].lookup(name)?,
+            };
+        }
+        Some(node_id)
+    }
+
+    /// The entries of a directory in name order, or `None` for a file.
+    pub fn children(&self, node_id: NodeId) -> Option<Vec<(&str, NodeId)>> {
+        let Node::Dir { children, .. } = &self[node_id] else {
+            return None;
+        };
+        let mut children: Vec<_> = children
+            .iter()
+            .map(|(name, child_id)| (name.as_str(), *child_id))
+            .collect();
+        children.sort_unstable();
+        Some(children)
+    }
+
+    /// Every node whose full path matches `pattern`, in depth-first order.
+    ///
+    /// `?` matches one character and `*` any run of characters within a
+    /// path segment, while `**` also crosses `/`. A pattern without a `/` is
+    /// matched against names alone, like `find -name`.
+    pub fn find(&self, pattern: &str) -> Vec<(String, NodeId)> {
+        let by_name = !pattern.contains('/');
+        let mut found = vec![];
+        self.visit(|_, node_id, name, path| {
+            let full = full_path(name, path);
+            let subject = if by_name { name } else { &full };
+            if glob_match(pattern.as_bytes(), subject.as_bytes()) {
+                found.push((full, node_id));
+            }
+        });
+        found
+    }
+
+    /// Every file larger than `threshold`, in depth-first order.
+    pub fn files_larger_than(&self, threshold: usize) -> Vec<(String, NodeId)> {
+        let mut found = vec![];
+        self.visit(|fs, node_id, name, path| {
+            if let Node::File { size, .. } = &fs[node_id] {
+                if *size > threshold {
+                    found.push((full_path(name, path), node_id));
+                }
+            }
+        });
+        found
+    }
+}
+
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    // matches[i][j] records whether pattern[i..] matches text[j..].
+    let mut matches = vec![vec![false; text.len() + 1]; pattern.len() + 1];
+    matches[pattern.len()][text.len()] = true;
+
+    for i in (0..pattern.len()).rev() {
+        for j in (0..=text.len()).rev() {
+            let next = text.get(j);
+            matches[i][j] = match &pattern[i..] {
+                [b'*', b'*', rest @ ..] => {
+                    // `**/` may also stand for no directories at all.
+                    (rest.first() == Some(&b'/') && matches[i + 3][j])
+                        || matches[i + 2][j]
+                        || (next.is_some() && matches[i][j + 1])
+                }
+                [b'*', ..] => {
+                    matches[i + 1][j] || (next.is_some_and(|c| *c != b'/') && matches[i][j + 1])
+                }
+                [b'?', ..] => next.is_some_and(|c| *c != b'/') && matches[i + 1][j + 1],
+                [c, ..] => next == Some(c) && matches[i + 1][j + 1],
+                [] => unreachable!(),
+            };
+        }
+    }
+
+    matches[0][0]
+}
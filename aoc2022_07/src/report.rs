@@ -0,0 +1,86 @@
+use std::{cmp::Reverse, fmt::Write};
+
+use crate::{FileSystem, Node, NodeId};
+
+/// Draws the filesystem in the same style as the puzzle's example, with the
+/// total size of each directory alongside the file sizes.
+pub fn tree(fs: &FileSystem) -> String {
+    let mut out = String::new();
+    fs.visit(|fs, node_id, name, path| {
+        let indent = "  ".repeat(path.len());
+        let _ = match &fs[node_id] {
+            Node::Dir { .. } => {
+                writeln!(
+                    out,
+                    "{indent}- {name} (dir, size={})",
+                    fs.total_size(node_id)
+                )
+            }
+            Node::File { size, .. } => writeln!(out, "{indent}- {name} (file, size={size})"),
+        };
+    });
+    out
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum SortOrder {
+    /// Largest directories first.
+    Size,
+    /// Alphabetically by full path.
+    Name,
+}
+
+impl std::str::FromStr for SortOrder {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "size" => Ok(Self::Size),
+            "name" => Ok(Self::Name),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Lists the cumulative size and full path of every directory, like `du`.
+pub fn du(fs: &FileSystem, order: SortOrder) -> String {
+    let mut rows: Vec<(usize, String)> = vec![];
+    fs.visit(|fs, node_id, name, path| {
+        if let Node::Dir { .. } = &fs[node_id] {
+            rows.push((fs.total_size(node_id), full_path(name, path)));
+        }
+    });
+
+    match order {
+        SortOrder::Size => rows.sort_by(|(a_size, a_path), (b_size, b_path)| {
+            (Reverse(a_size), a_path).cmp(&(Reverse(b_size), b_path))
+        }),
+        SortOrder::Name => rows.sort_by(|(_, a), (_, b)| a.cmp(b)),
+    }
+
+    let width = rows
+        .iter()
+        .map(|(size, _)| size.to_string().len())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::new();
+    for (size, path) in rows {
+        let _ = writeln!(out, "{size:>width$}  {path}");
+    }
+    out
+}
+
+/// Joins a node's name onto the path of directories above it.
+pub fn full_path(name: &str, path: &[(&str, NodeId)]) -> String {
+    // The first entry of any non-empty path is the root itself.
+    let mut full = String::new();
+    for (dir_name, _) in path.iter().skip(1) {
+        full.push('/');
+        full.push_str(dir_name);
+    }
+    if !path.is_empty() {
+        full.push('/');
+    }
+    full.push_str(name);
+    full
+}
@@ -0,0 +1,324 @@
+use std::fmt;
+
+use crate::{FileSystem, Node, NodeId};
+
+#[derive(Debug)]
+pub struct SessionError {
+    /// One-based line number in the transcript.
+    pub line: usize,
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    UnknownCommand(String),
+    /// Listing output that doesn't follow an `ls`.
+    UnexpectedOutput,
+    /// A line that doesn't parse, including listed names that aren't a
+    /// single path segment.
+    Malformed,
+    NotFound(String),
+    NotADirectory(String),
+    AlreadyExists(String),
+    /// Removing or moving a directory the session is inside of, moving a
+    /// directory into itself or over a file, or removing a directory
+    /// without `-r`.
+    InvalidTarget(String),
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ErrorKind::UnknownCommand(cmd) => write!(f, "unknown command `{cmd}`"),
+            ErrorKind::UnexpectedOutput => write!(f, "output without a preceding `ls`"),
+            ErrorKind::Malformed => write!(f, "malformed line"),
+            ErrorKind::NotFound(path) => write!(f, "{path}: no such file or directory"),
+            ErrorKind::NotADirectory(path) => write!(f, "{path}: not a directory"),
+            ErrorKind::AlreadyExists(path) => write!(f, "{path}: already exists"),
+            ErrorKind::InvalidTarget(path) => write!(f, "{path}: invalid target"),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+/// Replays a shell transcript against a filesystem.
+///
+/// Supports `cd` (relative, absolute and multi-segment paths), `ls`,
+/// `mkdir [-p]`, `rm [-rRf]` and `mv`, with flags given apart or combined as
+/// in `rm -rf`. Listing the same directory twice updates the existing entries
+/// rather than replacing them.
+pub struct Session {
+    fs: FileSystem,
+    /// Directories from the root down to the current one.
+    cwd: Vec<NodeId>,
+    listing: bool,
+}
+
+impl Session {
+    pub fn replay(transcript: &str) -> Result<FileSystem, SessionError> {
+        let mut session = Session {
+            fs: FileSystem::default(),
+            cwd: vec![],
+            listing: false,
+        };
+        session.cwd.push(session.fs.root_id());
+
+        for (idx, line) in transcript.lines().enumerate() {
+            session.step(line).map_err(|kind| SessionError {
+                line: idx + 1,
+                kind,
+            })?;
+        }
+
+        Ok(session.fs)
+    }
+
+    fn step(&mut self, line: &str) -> Result<(), ErrorKind> {
+        let Some(command) = line.strip_prefix("$ ") else {
+            return self.record(line);
+        };
+        self.listing = false;
+
+        let mut words = command.split_whitespace();
+        let program = words.next().ok_or(ErrorKind::Malformed)?;
+        let (flags, args): (Vec<&str>, Vec<&str>) = words.partition(|word| word.starts_with('-'));
+        let flags: String = flags.iter().map(|flag| &flag[1..]).collect();
+        let known_flags = match program {
+            "mkdir" => Some("p"),
+            "rm" => Some("rRf"),
+            "ls" | "cd" | "mv" => Some(""),
+            _ => None,
+        };
+        if known_flags.is_some_and(|known| !flags.chars().all(|flag| known.contains(flag))) {
+            return Err(ErrorKind::Malformed);
+        }
+        let flag = |flag| flags.contains(flag);
+
+        match (program, args.as_slice()) {
+            ("ls", []) => {
+                self.listing = true;
+                Ok(())
+            }
+            ("cd", [path]) => {
+                let target = self.resolve(path)?;
+                self.expect_dir(*target.last().unwrap(), path)?;
+                self.cwd = target;
+                Ok(())
+            }
+            ("mkdir", [path]) => self.mkdir(path, flag('p')),
+            ("rm", [path]) => match self.rm(path, flag('r') || flag('R')) {
+                // As with a real shell, forcing ignores a missing target.
+                Err(ErrorKind::NotFound(_)) if flag('f') => Ok(()),
+                result => result,
+            },
+            ("mv", [src, dst]) => self.mv(src, dst),
+            ("ls" | "cd" | "mkdir" | "rm" | "mv", _) => Err(ErrorKind::Malformed),
+            _ => Err(ErrorKind::UnknownCommand(command.to_string())),
+        }
+    }
+
+    /// Records one line of `ls` output in the current directory.
+    fn record(&mut self, line: &str) -> Result<(), ErrorKind> {
+        if !self.listing {
+            return Err(ErrorKind::UnexpectedOutput);
+        }
+
+        let cwd = *self.cwd.last().unwrap();
+        let (size_or_dir, name) = line.split_once(' ').ok_or(ErrorKind::Malformed)?;
+        if name.is_empty() || name == "." || name == ".." || name.contains('/') {
+            return Err(ErrorKind::Malformed);
+        }
+        let existing = self.fs[cwd].lookup(name);
+        if size_or_dir == "dir" {
+            match existing {
+                Some(id) if self.is_dir(id) => {}
+                Some(_) => return Err(ErrorKind::AlreadyExists(name.to_string())),
+                None => {
+                    self.fs.add_dir(cwd, name);
+                }
+            }
+        } else {
+            let size: usize = size_or_dir.parse().map_err(|_| ErrorKind::Malformed)?;
+            match existing {
+                Some(id) if self.is_dir(id) => {
+                    return Err(ErrorKind::AlreadyExists(name.to_string()))
+                }
+                Some(id) => self.fs.set_file_size(id, size),
+                None => {
+                    self.fs.add_file(cwd, name, size);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn mkdir(&mut self, path: &str, parents: bool) -> Result<(), ErrorKind> {
+        let mut dirs = self.start_of(path);
+        let segments: Vec<&str> = segments(path).collect();
+        if segments.last().is_none_or(|segment| *segment == "..") {
+            // Only names an existing directory, such as `/`.
+            return if parents {
+                Ok(())
+            } else {
+                Err(ErrorKind::AlreadyExists(path.to_string()))
+            };
+        }
+        for (idx, segment) in segments.iter().enumerate() {
+            let last = idx == segments.len() - 1;
+            match *segment {
+                ".." => {
+                    if dirs.len() > 1 {
+                        dirs.pop();
+                    }
+                }
+                name => {
+                    let dir = *dirs.last().unwrap();
+                    match self.fs[dir].lookup(name) {
+                        Some(child) if !last || parents => {
+                            self.expect_dir(child, path)?;
+                            dirs.push(child);
+                        }
+                        Some(_) => return Err(ErrorKind::AlreadyExists(path.to_string())),
+                        None if last || parents => {
+                            dirs.push(self.fs.add_dir(dir, name));
+                        }
+                        None => return Err(ErrorKind::NotFound(path.to_string())),
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn rm(&mut self, path: &str, recursive: bool) -> Result<(), ErrorKind> {
+        let (parent, name) = self.resolve_parent(path)?;
+        let target = self.fs[parent]
+            .lookup(name)
+            .ok_or_else(|| ErrorKind::NotFound(path.to_string()))?;
+        if self.cwd.contains(&target) || (self.is_dir(target) && !recursive) {
+            return Err(ErrorKind::InvalidTarget(path.to_string()));
+        }
+
+        self.fs.detach(parent, name);
+        Ok(())
+    }
+
+    /// Moves `src` into `dst` if that's an existing directory, otherwise
+    /// renames it to `dst`. A file already there is replaced by a file, but
+    /// never by a directory.
+    fn mv(&mut self, src: &str, dst: &str) -> Result<(), ErrorKind> {
+        let (src_parent, src_name) = self.resolve_parent(src)?;
+        let target = self.fs[src_parent]
+            .lookup(src_name)
+            .ok_or_else(|| ErrorKind::NotFound(src.to_string()))?;
+        if self.cwd.contains(&target) {
+            return Err(ErrorKind::InvalidTarget(src.to_string()));
+        }
+
+        let (dst_dirs, dst_name) = match self.resolve(dst) {
+            Ok(dirs) if self.is_dir(*dirs.last().unwrap()) => (dirs, src_name),
+            Ok(_) | Err(ErrorKind::NotFound(_)) => self.resolve_parent_dirs(dst)?,
+            Err(e) => return Err(e),
+        };
+        if dst_dirs.contains(&target) {
+            return Err(ErrorKind::InvalidTarget(dst.to_string()));
+        }
+
+        let dst_parent = *dst_dirs.last().unwrap();
+        if let Some(existing) = self.fs[dst_parent].lookup(dst_name) {
+            if existing == target {
+                return Ok(());
+            }
+            if self.is_dir(existing) {
+                return Err(ErrorKind::AlreadyExists(dst.to_string()));
+            }
+            if self.is_dir(target) {
+                return Err(ErrorKind::InvalidTarget(dst.to_string()));
+            }
+        }
+
+        self.fs.detach(src_parent, src_name);
+        self.fs.attach(dst_parent, dst_name, target);
+        Ok(())
+    }
+
+    fn start_of(&self, path: &str) -> Vec<NodeId> {
+        if path.starts_with('/') {
+            vec![self.fs.root_id()]
+        } else {
+            self.cwd.clone()
+        }
+    }
+
+    /// Resolves a path to the chain of nodes from the root down to it.
+    fn resolve(&self, path: &str) -> Result<Vec<NodeId>, ErrorKind> {
+        let mut nodes = self.start_of(path);
+        for segment in segments(path) {
+            let current = *nodes.last().unwrap();
+            self.expect_dir(current, path)?;
+            match segment {
+                ".." => {
+                    if nodes.len() > 1 {
+                        nodes.pop();
+                    }
+                }
+                name => {
+                    let child = self.fs[current]
+                        .lookup(name)
+                        .ok_or_else(|| ErrorKind::NotFound(path.to_string()))?;
+                    nodes.push(child);
+                }
+            }
+        }
+        Ok(nodes)
+    }
+
+    /// Resolves everything but the last segment of a path, which must then
+    /// name an entry rather than `/`, `.` or `..`.
+    fn resolve_parent_dirs<'p>(&self, path: &'p str) -> Result<(Vec<NodeId>, &'p str), ErrorKind> {
+        let trimmed = path.trim_end_matches('/');
+        let (dir, name) = match trimmed.rsplit_once('/') {
+            Some(("", name)) => ("/", name),
+            Some((dir, name)) => (dir, name),
+            None => ("", trimmed),
+        };
+        if name.is_empty() || name == "." || name == ".." {
+            return Err(ErrorKind::InvalidTarget(path.to_string()));
+        }
+
+        let dirs = if dir.is_empty() {
+            self.cwd.clone()
+        } else {
+            self.resolve(dir)?
+        };
+        self.expect_dir(*dirs.last().unwrap(), path)?;
+        Ok((dirs, name))
+    }
+
+    fn resolve_parent<'p>(&self, path: &'p str) -> Result<(NodeId, &'p str), ErrorKind> {
+        let (dirs, name) = self.resolve_parent_dirs(path)?;
+        Ok((*dirs.last().unwrap(), name))
+    }
+
+    fn is_dir(&self, node_id: NodeId) -> bool {
+        matches!(self.fs[node_id], Node::Dir { .. })
+    }
+
+    fn expect_dir(&self, node_id: NodeId, path: &str) -> Result<(), ErrorKind> {
+        if self.is_dir(node_id) {
+            Ok(())
+        } else {
+            Err(ErrorKind::NotADirectory(path.to_string()))
+        }
+    }
+}
+
+/// The non-trivial segments of a path; `.` and empty segments are dropped.
+fn segments(path: &str) -> impl Iterator<Item = &str> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty() && *segment != ".")
+}
@@ -0,0 +1,110 @@
+use std::time::Instant;
+
+use crate::{DirectionSet, Forest, Height};
+
+/// Times the running-maximum and monotonic-stack passes on a generated
+/// forest, and compares them with the original walk outward from every tree
+/// on a smaller one, since that walk is quadratic in the side length.
+///
+/// Usage: `bench [side] [side for the original]`
+pub fn run<I: Iterator<Item = String>>(mut args: I) {
+    let side: usize = args.next().map_or(5000, |s| s.parse().unwrap());
+    let legacy_side: usize = args.next().map_or(500, |s| s.parse().unwrap());
+
+    let directions = DirectionSet::four_way();
+    let forest = generate(side);
+    let start = Instant::now();
+    let visible = forest
+        .visibility(&directions)
+        .iter()
+        .filter(|v| **v)
+        .count();
+    let scenic = forest
+        .scenic_scores(&directions)
+        .into_iter()
+        .max()
+        .unwrap_or(0);
+    println!(
+        "{side}x{side}: {:?} ({visible} visible, best score {scenic})",
+        start.elapsed()
+    );
+
+    let forest = generate(legacy_side);
+    let start = Instant::now();
+    let visible = forest
+        .visibility(&directions)
+        .iter()
+        .filter(|v| **v)
+        .count();
+    let scenic = forest
+        .scenic_scores(&directions)
+        .into_iter()
+        .max()
+        .unwrap_or(0);
+    println!("{legacy_side}x{legacy_side}: {:?}", start.elapsed());
+
+    let start = Instant::now();
+    let (legacy_visible, legacy_scenic) = legacy(&forest);
+    println!(
+        "{legacy_side}x{legacy_side} walking out from every tree: {:?}",
+        start.elapsed()
+    );
+
+    assert_eq!(visible, legacy_visible);
+    assert_eq!(scenic, legacy_scenic);
+}
+
+fn generate(side: usize) -> Forest {
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let heights = (0..side * side)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 10) as Height
+        })
+        .collect();
+    Forest {
+        width: side,
+        height: side,
+        heights,
+    }
+}
+
+/// The original approach: walk all four directions from every tree.
+fn legacy(forest: &Forest) -> (usize, u64) {
+    let (w, h) = (forest.width, forest.height);
+    let at = |x: usize, y: usize| forest.heights[y * w + x];
+
+    let mut visible = 0;
+    let mut best = 0;
+    for y in 0..h {
+        for x in 0..w {
+            let tree = at(x, y);
+            let lines: [Vec<Height>; 4] = [
+                (0..x).rev().map(|i| at(i, y)).collect(),
+                (x + 1..w).map(|i| at(i, y)).collect(),
+                (0..y).rev().map(|i| at(x, i)).collect(),
+                (y + 1..h).map(|i| at(x, i)).collect(),
+            ];
+
+            if lines
+                .iter()
+                .any(|line| line.iter().all(|other| *other < tree))
+            {
+                visible += 1;
+            }
+
+            let score: u64 = lines
+                .iter()
+                .map(|line| match line.iter().position(|other| *other >= tree) {
+                    Some(blocked) => blocked as u64 + 1,
+                    None => line.len() as u64,
+                })
+                .product();
+            best = best.max(score);
+        }
+    }
+
+    (visible, best)
+}
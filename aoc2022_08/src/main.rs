@@ -1,94 +1,182 @@
-use std::fmt::Debug;
+mod bench;
+mod parse;
+mod render;
+mod sight;
+
+use parse::GridFormat;
+use sight::{Direction, DirectionSet, Position};
 
 const INPUT: &str = include_str!("real_input.txt");
 
 fn main() {
+    let mut show_mask = false;
+    let mut show_heatmap = false;
+    let mut ppm_path = None;
+    let mut png_path = None;
+    let mut scale = 4;
+    let mut directions = DirectionSet::four_way();
+    let mut observer = None;
+    let mut format = GridFormat::Auto;
+    let mut path = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "bench" => {
+                bench::run(args);
+                return;
+            }
+            "--mask" => show_mask = true,
+            "--heatmap" => show_heatmap = true,
+            "--ppm" => ppm_path = args.next(),
+            "--png" => png_path = args.next(),
+            "--scale" => scale = args.next().unwrap().parse().unwrap(),
+            "--directions" => directions = args.next().unwrap().parse().unwrap(),
+            "--observer" => observer = args.next(),
+            "--format" => format = args.next().unwrap().parse().unwrap(),
+            _ => path = Some(arg),
+        }
+    }
+
+    // An optional path reads a heightmap at runtime instead.
+    let input = match path {
+        Some(path) => std::fs::read_to_string(path).unwrap(),
+        None => INPUT.to_string(),
+    };
+    let forest = match Forest::parse(&input, format) {
+        Ok(forest) => forest,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    let visible = forest.visibility(&directions);
+    let scores = forest.scenic_scores(&directions);
+
+    // An observer is given as `x,y,eye height`.
+    if let Some(observer) = observer {
+        let values: Vec<isize> = observer.split(',').map(|v| v.parse().unwrap()).collect();
+        let &[x, y, eye_height] = values.as_slice() else {
+            panic!("Observer should be x,y,height");
+        };
+        let seen = forest.visible_from((x, y), eye_height as i64, &directions);
+        println!("{} trees visible from ({x}, {y}): {seen:?}", seen.len());
+    }
+
+    if show_mask {
+        print!("{}", render::mask(&forest, &visible));
+    }
+    if show_heatmap {
+        print!("{}", render::ansi_heatmap(&forest, &scores));
+    }
+    if let Some(path) = ppm_path {
+        std::fs::write(path, render::ppm(&forest, &scores, scale)).unwrap();
+    }
+    if let Some(path) = png_path {
+        std::fs::write(path, render::png(&forest, &scores, scale)).unwrap();
+    }
+
     // part 1
     {
-        let part1 = treeval(
-            true,
-            |acc, self_h, other_h| *acc && other_h < self_h,
-            |d1, d2, d3, d4| (d1 || d2 || d3 || d4) as i32,
-            0,
-            |acc, t| *acc + t,
-        );
+        let part1 = visible.iter().filter(|v| **v).count();
         dbg!(part1);
     }
 
     // part 2
     {
-        let part2 = treeval(
-            (0, true),
-            |(acc_score, cont), self_h, other_h| {
-                if !cont {
-                    (*acc_score, *cont)
-                } else {
-                    (acc_score + 1, other_h < self_h)
-                }
-            },
-            |d1, d2, d3, d4| d1.0 * d2.0 * d3.0 * d4.0,
-            0,
-            |acc, t| {
-                if t > *acc {
-                    t
-                } else {
-                    *acc
-                }
-            },
-        );
-        dbg!(part2);
+        let (best_idx, part2) = scores
+            .iter()
+            .enumerate()
+            .max_by_key(|(_, score)| **score)
+            .unwrap();
+        let best_tree = (best_idx % forest.width, best_idx / forest.width);
+        dbg!(part2, best_tree);
     }
 }
 
-fn treeval<
-    R1: Clone + Debug,
-    R2: Debug,
-    R3: Debug,
-    F1: Fn(&R1, u8, u8) -> R1,
-    F2: Fn(R1, R1, R1, R1) -> R2,
-    F3: Fn(&R3, R2) -> R3,
->(
-    dir_acc: R1,
-    dir: F1,
-    tree_score: F2,
-    result_acc: R3,
-    result_fold: F3,
-) -> R3 {
-    let mut rows: Vec<Vec<u8>> = vec![];
-    for line in INPUT.lines() {
-        rows.push(line.bytes().map(|b| b - b'0').collect());
+type Height = u32;
+
+/// Tree heights in row-major order.
+#[derive(Debug, Clone)]
+struct Forest {
+    width: usize,
+    height: usize,
+    heights: Vec<Height>,
+}
+
+impl Forest {
+    fn contains(&self, (x, y): Position) -> bool {
+        (0..self.width as isize).contains(&x) && (0..self.height as isize).contains(&y)
     }
-    let width = rows[0].len();
-    let height = rows.len();
-
-    let mut result = result_acc;
-    for y in 0..height {
-        for x in 0..width {
-            let h = rows[y][x];
-            let mut neg_x = dir_acc.clone();
-            for i in (0..x).rev() {
-                neg_x = dir(&neg_x, h, rows[y][i]);
-            }
 
-            let mut pos_x = dir_acc.clone();
-            for i in (x + 1)..width {
-                pos_x = dir(&pos_x, h, rows[y][i]);
-            }
+    fn index(&self, (x, y): Position) -> usize {
+        y as usize * self.width + x as usize
+    }
 
-            let mut neg_y = dir_acc.clone();
-            for i in (0..y).rev() {
-                neg_y = dir(&neg_y, h, rows[i][x]);
-            }
+    /// Indices of the trees from `from` onwards, stepping by `step` until
+    /// leaving the forest.
+    fn walk(&self, from: Position, step: Direction) -> impl Iterator<Item = usize> + '_ {
+        std::iter::successors(Some(from), move |(x, y)| Some((x + step.0, y + step.1)))
+            .take_while(|pos| self.contains(*pos))
+            .map(|pos| self.index(pos))
+    }
 
-            let mut pos_y = dir_acc.clone();
-            for i in (y + 1)..height {
-                pos_y = dir(&pos_y, h, rows[i][x]);
-            }
+    /// Every line of trees running against `direction`, each starting from
+    /// the tree at the edge that `direction` points towards. Together they
+    /// cover each tree exactly once.
+    fn lines_of_sight(&self, direction: Direction) -> impl Iterator<Item = Vec<usize>> + '_ {
+        let (dx, dy) = direction;
+        (0..self.height as isize)
+            .flat_map(move |y| (0..self.width as isize).map(move |x| (x, y)))
+            .filter(move |(x, y)| !self.contains((x + dx, y + dy)))
+            .map(move |edge| self.walk(edge, (-dx, -dy)).collect())
+    }
 
-            let score = tree_score(neg_x, pos_x, neg_y, pos_y);
-            result = result_fold(&result, score);
+    /// Whether each tree can be seen from outside the forest looking along
+    /// any of `directions`, found with a running maximum along every line.
+    fn visibility(&self, directions: &DirectionSet) -> Vec<bool> {
+        let mut visible = vec![false; self.heights.len()];
+        for direction in directions.iter() {
+            for line in self.lines_of_sight(*direction) {
+                let mut tallest: Option<Height> = None;
+                for idx in line {
+                    let h = self.heights[idx];
+                    if tallest.is_none_or(|t| h > t) {
+                        visible[idx] = true;
+                        tallest = Some(h);
+                    }
+                }
+            }
         }
+        visible
     }
 
-    result
+    /// The product of each tree's viewing distances along `directions`,
+    /// counted in steps and saturating at `u64::MAX`.
+    ///
+    /// Walking a line, a stack holds the trees that could still block the
+    /// view back along it, in decreasing height. Shorter trees are popped,
+    /// leaving the nearest tree at least as tall on top.
+    fn scenic_scores(&self, directions: &DirectionSet) -> Vec<u64> {
+        let mut scores = vec![1u64; self.heights.len()];
+        let mut blockers: Vec<(usize, Height)> = vec![];
+        for direction in directions.iter() {
+            for line in self.lines_of_sight(*direction) {
+                blockers.clear();
+                for (i, idx) in line.into_iter().enumerate() {
+                    let h = self.heights[idx];
+                    while blockers.last().is_some_and(|(_, bh)| *bh < h) {
+                        blockers.pop();
+                    }
+                    let distance = match blockers.last() {
+                        Some((bi, _)) => i - bi,
+                        None => i,
+                    };
+                    scores[idx] = scores[idx].saturating_mul(distance as u64);
+                    blockers.push((i, h));
+                }
+            }
+        }
+        scores
+    }
 }
@@ -0,0 +1,137 @@
+use std::fmt;
+
+use crate::{Forest, Height};
+
+#[derive(Debug, Clone, Copy)]
+pub enum GridFormat {
+    /// One digit per tree, as in the puzzle input.
+    Digits,
+    /// Heights separated by spaces or tabs.
+    Whitespace,
+    /// Heights separated by commas.
+    Csv,
+    /// Picks one of the above from the first row.
+    Auto,
+}
+
+impl std::str::FromStr for GridFormat {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "digits" => Ok(Self::Digits),
+            "whitespace" => Ok(Self::Whitespace),
+            "csv" => Ok(Self::Csv),
+            "auto" => Ok(Self::Auto),
+            _ => Err(()),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ParseError {
+    /// A cell that isn't a non-negative number. Rows are line numbers in the
+    /// input, and both rows and columns count from 1.
+    InvalidHeight {
+        row: usize,
+        col: usize,
+        text: String,
+    },
+    /// A row with a different number of trees from the first.
+    Ragged {
+        row: usize,
+        expected: usize,
+        found: usize,
+    },
+    /// Input without a single tree.
+    Empty,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidHeight { row, col, text } => {
+                write!(f, "row {row}, column {col}: invalid height {text:?}")
+            }
+            ParseError::Ragged {
+                row,
+                expected,
+                found,
+            } => write!(f, "row {row}: expected {expected} trees, found {found}"),
+            ParseError::Empty => write!(f, "no trees found"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl std::str::FromStr for Forest {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Forest::parse(s, GridFormat::Auto)
+    }
+}
+
+impl Forest {
+    /// Reads a heightmap, one row per line. Blank lines are skipped, every
+    /// row must be as wide as the first, and there must be at least one tree.
+    pub fn parse(s: &str, format: GridFormat) -> Result<Self, ParseError> {
+        let first = s.lines().find(|line| !line.trim().is_empty());
+        let format = match (format, first) {
+            (GridFormat::Auto, Some(first)) if first.contains(',') => GridFormat::Csv,
+            (GridFormat::Auto, Some(first)) if first.trim().contains(char::is_whitespace) => {
+                GridFormat::Whitespace
+            }
+            (GridFormat::Auto, _) => GridFormat::Digits,
+            (format, _) => format,
+        };
+
+        let mut heights = vec![];
+        let mut width = None;
+        let mut rows = 0;
+        for (row, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let cells: Vec<&str> = match format {
+                GridFormat::Digits => line
+                    .char_indices()
+                    .map(|(idx, c)| &line[idx..idx + c.len_utf8()])
+                    .collect(),
+                GridFormat::Whitespace => line.split_whitespace().collect(),
+                GridFormat::Csv => line.split(',').map(str::trim).collect(),
+                GridFormat::Auto => unreachable!(),
+            };
+
+            let expected = *width.get_or_insert(cells.len());
+            if cells.len() != expected {
+                return Err(ParseError::Ragged {
+                    row: row + 1,
+                    expected,
+                    found: cells.len(),
+                });
+            }
+
+            for (col, cell) in cells.into_iter().enumerate() {
+                let height: Height = cell.parse().map_err(|_| ParseError::InvalidHeight {
+                    row: row + 1,
+                    col: col + 1,
+                    text: cell.to_string(),
+                })?;
+                heights.push(height);
+            }
+            rows += 1;
+        }
+        if heights.is_empty() {
+            return Err(ParseError::Empty);
+        }
+
+        Ok(Self {
+            width: width.unwrap_or(0),
+            height: rows,
+            heights,
+        })
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt::Write;
+
+use crate::Forest;
+
+/// One character per tree: `#` if it can be seen from outside, `.` if not.
+pub fn mask(forest: &Forest, visible: &[bool]) -> String {
+    let mut out = String::new();
+    for row in visible.chunks(forest.width.max(1)) {
+        out.extend(row.iter().map(|v| if *v { '#' } else { '.' }));
+        out.push('\n');
+    }
+    out
+}
+
+/// Each tree's height drawn over a background coloured by its scenic score.
+pub fn ansi_heatmap(forest: &Forest, scores: &[u64]) -> String {
+    let max = scores.iter().copied().max().unwrap_or(0);
+    let cell_width = forest
+        .heights
+        .iter()
+        .map(|h| h.to_string().len())
+        .max()
+        .unwrap_or(1);
+    let mut out = String::new();
+    for (row_heights, row_scores) in forest
+        .heights
+        .chunks(forest.width.max(1))
+        .zip(scores.chunks(forest.width.max(1)))
+    {
+        for (h, score) in row_heights.iter().zip(row_scores) {
+            let (r, g, b) = heat(*score, max);
+            let _ = write!(
+                out,
+                "\x1b[48;2;{r};{g};{b}m\x1b[38;2;255;255;255m{h:>cell_width$}"
+            );
+        }
+        out.push_str("\x1b[0m\n");
+    }
+    out
+}
+
+/// The heatmap as a binary PPM image, `scale` pixels square per tree.
+pub fn ppm(forest: &Forest, scores: &[u64], scale: usize) -> Vec<u8> {
+    let (width, height, pixels) = pixels(forest, scores, scale);
+    let mut out = format!("P6\n{width} {height}\n255\n").into_bytes();
+    out.extend(pixels);
+    out
+}
+
+/// The heatmap as a PNG image, `scale` pixels square per tree.
+pub fn png(forest: &Forest, scores: &[u64], scale: usize) -> Vec<u8> {
+    let (width, height, pixels) = pixels(forest, scores, scale);
+    aoc2022_png::rgb(width, height, &pixels)
+}
+
+fn pixels(forest: &Forest, scores: &[u64], scale: usize) -> (usize, usize, Vec<u8>) {
+    let scale = scale.max(1);
+    let max = scores.iter().copied().max().unwrap_or(0);
+    let (width, height) = (forest.width * scale, forest.height * scale);
+    let mut pixels = Vec::with_capacity(width * height * 3);
+    for y in 0..height {
+        for x in 0..width {
+            let (r, g, b) = heat(scores[(y / scale) * forest.width + x / scale], max);
+            pixels.extend([r, g, b]);
+        }
+    }
+    (width, height, pixels)
+}
+
+/// Maps a score onto a dark blue to yellow ramp. Scores are heavily skewed
+/// towards zero, so the ramp is logarithmic.
+fn heat(score: u64, max: u64) -> (u8, u8, u8) {
+    let t = if max == 0 {
+        0.0
+    } else {
+        ((score as f64).ln_1p() / (max as f64).ln_1p()) as f32
+    };
+    let lerp = |from: f32, to: f32| (from + (to - from) * t).round() as u8;
+    (lerp(20.0, 255.0), lerp(20.0, 220.0), lerp(90.0, 40.0))
+}
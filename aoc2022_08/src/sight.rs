@@ -0,0 +1,117 @@
+use std::ops::Deref;
+
+use crate::Forest;
+
+/// A step between trees as `(dx, dy)`, with `y` growing down the rows.
+pub type Direction = (isize, isize);
+pub type Position = (isize, isize);
+
+/// The directions trees are looked at along. Each is a primitive step, so a
+/// line through it passes exactly the trees on the true line of sight.
+#[derive(Debug, Clone)]
+pub struct DirectionSet(Vec<Direction>);
+
+impl DirectionSet {
+    /// Up, down, left and right, as in the puzzle.
+    pub fn four_way() -> Self {
+        Self(vec![(0, -1), (0, 1), (-1, 0), (1, 0)])
+    }
+
+    /// The four axes plus the diagonals.
+    pub fn eight_way() -> Self {
+        let mut directions = Self::four_way();
+        directions.0.extend([(-1, -1), (1, -1), (-1, 1), (1, 1)]);
+        directions
+    }
+
+    /// Both ways along each slope `rise / run`, reduced to lowest terms.
+    pub fn slopes(slopes: &[(isize, isize)]) -> Option<Self> {
+        let mut directions = vec![];
+        for (rise, run) in slopes {
+            let divisor = gcd(*rise, *run);
+            if divisor == 0 {
+                return None;
+            }
+            let step = (run / divisor, rise / divisor);
+            for direction in [step, (-step.0, -step.1)] {
+                if !directions.contains(&direction) {
+                    directions.push(direction);
+                }
+            }
+        }
+        Some(Self(directions))
+    }
+}
+
+impl Deref for DirectionSet {
+    type Target = [Direction];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+/// Parses `4`, `8`, or a comma separated list of `rise/run` slopes such as
+/// `1/2,-1/2`, each looked along both ways.
+impl std::str::FromStr for DirectionSet {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "4" => Ok(Self::four_way()),
+            "8" => Ok(Self::eight_way()),
+            _ => {
+                let mut slopes = vec![];
+                for slope in s.split(',') {
+                    let (rise, run) = slope.split_once('/').ok_or(())?;
+                    slopes.push((
+                        rise.trim().parse().map_err(|_| ())?,
+                        run.trim().parse().map_err(|_| ())?,
+                    ));
+                }
+                Self::slopes(&slopes).ok_or(())
+            }
+        }
+    }
+}
+
+impl Forest {
+    /// The trees an observer standing at `at` with their eyes `eye_height`
+    /// above the ground can see along `directions`.
+    ///
+    /// Along each direction a tree is visible if its top rises above the
+    /// sight line to every nearer tree, compared by gradient. Trees are
+    /// returned as `(x, y)`.
+    pub fn visible_from(
+        &self,
+        at: Position,
+        eye_height: i64,
+        directions: &DirectionSet,
+    ) -> Vec<Position> {
+        let mut seen = vec![];
+        for (dx, dy) in directions.iter() {
+            // The steepest gradient so far, as (rise, steps).
+            let mut horizon: Option<(i64, i64)> = None;
+            let first = (at.0 + dx, at.1 + dy);
+            for (step, idx) in self.walk(first, (*dx, *dy)).enumerate() {
+                let rise = self.heights[idx] as i64 - eye_height;
+                let steps = step as i64 + 1;
+                if horizon.is_none_or(|(h_rise, h_steps)| rise * h_steps > h_rise * steps) {
+                    seen.push(((idx % self.width) as isize, (idx / self.width) as isize));
+                    horizon = Some((rise, steps));
+                }
+            }
+        }
+        seen.sort_unstable_by_key(|(x, y)| (*y, *x));
+        seen.dedup();
+        seen
+    }
+}
+
+fn gcd(a: isize, b: isize) -> isize {
+    let (mut a, mut b) = (a.abs(), b.abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
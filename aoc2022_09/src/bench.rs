@@ -0,0 +1,74 @@
+use std::{collections::HashSet, time::Instant};
+
+use crate::{physics::Physics, tail_cells, Instruction, Point, Vec2};
+
+/// Times counting the tail's cells on a generated random walk, against
+/// stepping every knot for every move into a `HashSet`.
+///
+/// Usage: `bench [steps] [knots]`
+pub fn run<I: Iterator<Item = String>>(mut args: I) {
+    let steps: usize = args.next().map_or(2_000_000, |s| s.parse().unwrap());
+    let knots: usize = args.next().map_or(200, |s| s.parse().unwrap());
+
+    let instructions = generate(steps);
+    let physics = Physics::default();
+
+    let start = Instant::now();
+    let cells = tail_cells(&instructions, knots, &physics);
+    println!(
+        "{steps} steps, {knots} knots: {:?} ({cells} cells)",
+        start.elapsed()
+    );
+
+    let start = Instant::now();
+    let legacy_cells = legacy(&instructions, knots, &physics);
+    println!("stepping every knot into a HashSet: {:?}", start.elapsed());
+
+    assert_eq!(cells, legacy_cells);
+}
+
+/// Moves in all eight directions adding up to `steps`, mostly short but
+/// with the odd long run.
+fn generate(steps: usize) -> Vec<Instruction> {
+    let mut state: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    let mut instructions = vec![];
+    let mut remaining = steps;
+    while remaining > 0 {
+        let (_, dir) = Vec2::MOVES[next() as usize % Vec2::MOVES.len()];
+        let count = if next() % 10 == 0 {
+            next() % 1000 + 1
+        } else {
+            next() % 20 + 1
+        };
+        let count = (count as usize).min(remaining);
+        instructions.push(Instruction { dir, count });
+        remaining -= count;
+    }
+    instructions
+}
+
+/// The original approach: drag every knot along for every step.
+fn legacy(instructions: &[Instruction], len: usize, physics: &Physics) -> usize {
+    let mut knots = vec![Vec2 { x: 0, y: 0 }; len];
+    let mut visited: HashSet<Vec2> = HashSet::new();
+    visited.insert(knots[len - 1]);
+    for instruction in instructions {
+        for _ in 0..instruction.count {
+            knots[0] = knots[0] + instruction.dir;
+            for i in 1..len {
+                knots[i] = physics
+                    .follow
+                    .follow(knots[i - 1], knots[i], physics.slack(i));
+            }
+            visited.insert(knots[len - 1]);
+        }
+    }
+    visited.len()
+}
@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use crate::point::{Point, Vec2};
+
+/// Cells along each side of a chunk. A word holds a line of cells along
+/// the first axis, so a chunk is a word for each line across the rest.
+const CHUNK: i32 = 64;
+
+/// A set of cells stored as one bit each, in square (or cubic) chunks
+/// allocated as the cells in them are first added. Neighbouring cells share
+/// a chunk, so a rope crawling along mostly stays within the chunk it used
+/// last and skips the hash lookup.
+#[derive(Debug, Clone)]
+pub struct Bitmap<P: Point = Vec2> {
+    /// Each chunk's words one after the other.
+    words: Vec<u64>,
+    /// The first word of each chunk by chunk coordinates.
+    index: HashMap<P, usize>,
+    /// The chunk used last, with its coordinates.
+    last: Option<(P, usize)>,
+    len: usize,
+}
+
+impl<P: Point> Bitmap<P> {
+    pub fn new() -> Self {
+        Self {
+            words: vec![],
+            index: HashMap::new(),
+            last: None,
+            len: 0,
+        }
+    }
+
+    /// Number of cells in the set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Adds a cell, returning whether it was new.
+    pub fn insert(&mut self, pos: P) -> bool {
+        let (key, word, mask) = split(pos);
+        let chunk = match self.last {
+            Some((last_key, chunk)) if last_key == key => chunk,
+            _ => {
+                let next = self.words.len();
+                let chunk = *self.index.entry(key).or_insert(next);
+                if chunk == next {
+                    let chunk_words = (CHUNK as usize).pow(P::DIMENSIONS as u32 - 1);
+                    self.words.resize(next + chunk_words, 0);
+                }
+                self.last = Some((key, chunk));
+                chunk
+            }
+        };
+
+        let word = &mut self.words[chunk + word];
+        let new = *word & mask == 0;
+        *word |= mask;
+        self.len += new as usize;
+        new
+    }
+}
+
+/// The coordinates of the chunk holding `pos`, with its word and bit there.
+fn split<P: Point>(pos: P) -> (P, usize, u64) {
+    let key = pos.map(|c| c.div_euclid(CHUNK));
+    let word = pos.coords().skip(1).fold(0, |word, c| {
+        word * CHUNK as usize + c.rem_euclid(CHUNK) as usize
+    });
+    let mask = 1 << pos.coord(0).rem_euclid(CHUNK);
+    (key, word, mask)
+}
@@ -0,0 +1,154 @@
+use std::{
+    collections::HashMap,
+    io::{self, Write},
+};
+
+/// Writes a looping animated GIF frame by frame.
+pub struct GifWriter<W: Write> {
+    out: W,
+    width: u16,
+    height: u16,
+    min_code_size: u8,
+}
+
+impl<W: Write> GifWriter<W> {
+    /// Starts an animation with a global palette of up to 256 colours.
+    pub fn new(mut out: W, width: u16, height: u16, palette: &[[u8; 3]]) -> io::Result<Self> {
+        // The palette has to be a power of two of at least two colours.
+        let bits = palette.len().max(2).next_power_of_two().trailing_zeros() as u8;
+        if bits > 8 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "too many colours",
+            ));
+        }
+
+        out.write_all(b"GIF89a")?;
+        out.write_all(&width.to_le_bytes())?;
+        out.write_all(&height.to_le_bytes())?;
+        // Global colour table present, 8 bits per primary, with its size.
+        out.write_all(&[0x80 | 0x70 | (bits - 1), 0, 0])?;
+        for idx in 0..1 << bits {
+            out.write_all(palette.get(idx).unwrap_or(&[0, 0, 0]))?;
+        }
+
+        // Loop forever.
+        out.write_all(b"\x21\xff\x0bNETSCAPE2.0\x03\x01\x00\x00\x00")?;
+
+        Ok(Self {
+            out,
+            width,
+            height,
+            min_code_size: bits.max(2),
+        })
+    }
+
+    /// Adds a frame of palette indices in row-major order, shown for
+    /// `delay_cs` hundredths of a second.
+    pub fn frame(&mut self, pixels: &[u8], delay_cs: u16) -> io::Result<()> {
+        if pixels.len() != self.width as usize * self.height as usize {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "frame doesn't match the image size",
+            ));
+        }
+
+        // Graphic control extension carrying the delay.
+        self.out.write_all(&[0x21, 0xf9, 0x04, 0x00])?;
+        self.out.write_all(&delay_cs.to_le_bytes())?;
+        self.out.write_all(&[0x00, 0x00])?;
+
+        // Image descriptor covering the whole canvas, using the global palette.
+        self.out.write_all(&[0x2c, 0, 0, 0, 0])?;
+        self.out.write_all(&self.width.to_le_bytes())?;
+        self.out.write_all(&self.height.to_le_bytes())?;
+        self.out.write_all(&[0x00, self.min_code_size])?;
+
+        let data = lzw_encode(pixels, self.min_code_size);
+        for block in data.chunks(255) {
+            self.out.write_all(&[block.len() as u8])?;
+            self.out.write_all(block)?;
+        }
+        self.out.write_all(&[0x00])
+    }
+
+    pub fn finish(mut self) -> io::Result<()> {
+        self.out.write_all(&[0x3b])?;
+        self.out.flush()
+    }
+}
+
+/// Variable-width LZW as used by GIF, starting over with a clear code
+/// whenever the 12-bit code table fills up.
+fn lzw_encode(pixels: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear: u16 = 1 << min_code_size;
+    let end = clear + 1;
+
+    let mut out = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut next_code = end + 1;
+    let mut code_size = min_code_size + 1;
+
+    out.write(clear, code_size);
+    let mut prefix: Option<u16> = None;
+    for pixel in pixels {
+        let Some(current) = prefix else {
+            prefix = Some(*pixel as u16);
+            continue;
+        };
+        if let Some(code) = table.get(&(current, *pixel)) {
+            prefix = Some(*code);
+            continue;
+        }
+
+        out.write(current, code_size);
+        if next_code < 4096 {
+            table.insert((current, *pixel), next_code);
+            next_code += 1;
+            // The decoder's table lags one code behind ours, so widen only
+            // once it will also need the extra bit.
+            if next_code > 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+        } else {
+            out.write(clear, code_size);
+            table.clear();
+            next_code = end + 1;
+            code_size = min_code_size + 1;
+        }
+        prefix = Some(*pixel as u16);
+    }
+
+    if let Some(current) = prefix {
+        out.write(current, code_size);
+    }
+    out.write(end, code_size);
+    out.finish()
+}
+
+/// Packs codes least significant bit first.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    acc: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.acc |= (code as u32) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.acc as u8);
+            self.acc >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.acc as u8);
+        }
+        self.bytes
+    }
+}
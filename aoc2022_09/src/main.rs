@@ -1,89 +1,260 @@
-use std::collections::HashSet;
+mod bench;
+mod bitmap;
+mod gif;
+mod physics;
+mod point;
+mod visits;
+mod visualise;
+
+use bitmap::Bitmap;
+use physics::{FollowRule, Physics};
+use point::{Point, Vec2, Vec3};
+use visits::Visits;
 
 const INPUT: &str = include_str!("real_input.txt");
 
 fn main() {
+    let mut options = visualise::Options::default();
+    let mut knots = 10;
+    let mut slack = vec![1];
+    let mut follow = "chebyshev".to_string();
+    let mut three_d = false;
+    let mut visualise = false;
+    let mut knot = None;
+    let mut top = 5;
+    let mut path = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "bench" => {
+                bench::run(args);
+                return;
+            }
+            "--knots" => knots = args.next().unwrap().parse().unwrap(),
+            "--slack" => slack = physics::parse_slack(&args.next().unwrap()).unwrap(),
+            "--follow" => follow = args.next().unwrap(),
+            "--3d" => three_d = true,
+            "--knot" => knot = Some(args.next().unwrap().parse().unwrap()),
+            "--top" => top = args.next().unwrap().parse().unwrap(),
+            "--play" => {
+                options.play = true;
+                visualise = true;
+            }
+            "--delay" => options.delay_ms = args.next().unwrap().parse().unwrap(),
+            "--every" => options.every = args.next().unwrap().parse().unwrap(),
+            "--frames" => {
+                options.frames_dir = args.next();
+                visualise = true;
+            }
+            "--gif" => {
+                options.gif_path = args.next();
+                visualise = true;
+            }
+            "--scale" => options.scale = args.next().unwrap().parse().unwrap(),
+            _ => path = Some(arg),
+        }
+    }
+    if knot.is_some_and(|knot| knot >= knots) {
+        eprintln!("--knot counts from 0 at the head, so it must be below --knots {knots}");
+        std::process::exit(1);
+    }
+    if three_d && visualise {
+        eprintln!("--play, --frames and --gif only draw 2D ropes, so they can't be used with --3d");
+        std::process::exit(1);
+    }
+
+    // An optional path reads the moves at runtime instead, e.g. to watch
+    // the examples.
+    let input = match path {
+        Some(path) => std::fs::read_to_string(path).unwrap(),
+        None => INPUT.to_string(),
+    };
+
+    if three_d {
+        let physics = Physics::<Vec3> {
+            slack,
+            follow: physics::parse_follow(&follow).unwrap(),
+        };
+        solve(&input, &physics, knots, knot, top);
+        return;
+    }
+
+    let physics = Physics::<Vec2> {
+        slack,
+        follow: physics::parse_follow(&follow).unwrap(),
+    };
+    if visualise {
+        let instructions: Vec<Instruction> = input.lines().map(|s| s.parse().unwrap()).collect();
+        visualise::run(&instructions, knots, &physics, &options).unwrap();
+        return;
+    }
+    solve(&input, &physics, knots, knot, top);
+}
+
+fn solve<P: Point>(
+    input: &str,
+    physics: &Physics<P>,
+    knots: usize,
+    knot: Option<usize>,
+    top: usize,
+) {
+    let instructions: Vec<Instruction<P>> = input.lines().map(|s| s.parse().unwrap()).collect();
+
+    // Where one knot of a `--knots` long rope went.
+    if let Some(knot) = knot {
+        let visits = rope_sim(&instructions, knots, physics);
+        let cells = visits.cells(knot);
+        let most_visited = visits.most_visited(knot, top);
+        dbg!(knot, cells, most_visited);
+    }
+
     // part 1
     {
-        let part1 = rope_sim(2);
+        let part1 = tail_cells(&instructions, 2, physics);
         dbg!(part1);
     }
 
     // part 2
     {
-        let part2 = rope_sim(10);
+        let part2 = tail_cells(&instructions, 10, physics);
         dbg!(part2);
     }
 }
 
-fn rope_sim(len: usize) -> usize {
-    let mut knots = vec![Vec2 { x: 0, y: 0 }; len];
-
-    let mut visited: HashSet<Vec2> = HashSet::new();
-    visited.insert(Vec2 { x: 0, y: 0 });
+fn rope_sim<P: Point>(
+    instructions: &[Instruction<P>],
+    len: usize,
+    physics: &Physics<P>,
+) -> Visits<P> {
+    let mut rope = Rope::new(len, physics);
+    let mut visits = Visits::new(&rope);
 
-    for line in INPUT.lines() {
-        let (direction, count_str) = line.split_once(" ").unwrap();
-        let count: usize = count_str.parse().unwrap();
-
-        let dir_vec = match direction {
-            "U" => Vec2 { x: 0, y: 1 },
-            "D" => Vec2 { x: 0, y: -1 },
-            "L" => Vec2 { x: -1, y: 0 },
-            "R" => Vec2 { x: 1, y: 0 },
-            _ => panic!("Unknown dir"),
-        };
-
-        for _ in 0..count {
-            knots[0] = knots[0] + dir_vec;
-            for i in 1..len {
-                knots[i] = drag_tail(knots[i - 1], knots[i]);
-            }
-            visited.insert(knots[len - 1]);
+    for instruction in instructions {
+        for _ in 0..instruction.count {
+            rope.step(instruction.dir);
+            visits.record(&rope);
         }
     }
 
-    visited.len()
+    visits
 }
 
-fn drag_tail(head: Vec2, tail: Vec2) -> Vec2 {
-    let delta = head - tail;
+/// How many cells the tail visits, without keeping track of anything else.
+fn tail_cells<P: Point>(
+    instructions: &[Instruction<P>],
+    len: usize,
+    physics: &Physics<P>,
+) -> usize {
+    let mut rope = Rope::new(len, physics);
+    let mut trail = Bitmap::new();
+    trail.insert(rope.tail());
 
-    if delta.x.abs() <= 1 && delta.y.abs() <= 1 {
-        tail
-    } else {
-        let step = Vec2 {
-            x: delta.x.min(1).max(-1),
-            y: delta.y.min(1).max(-1),
-        };
-        tail + step
+    for instruction in instructions {
+        rope.advance(instruction.dir, instruction.count, &mut trail);
     }
+
+    trail.len()
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
-struct Vec2 {
-    x: i32,
-    y: i32,
+#[derive(Debug, Clone)]
+struct Rope<P: Point = Vec2> {
+    /// The head first, then each knot behind it.
+    knots: Vec<P>,
+    /// The slack between each knot and the one ahead of it.
+    slack: Vec<i32>,
+    follow: &'static dyn FollowRule<P>,
 }
 
-impl std::ops::Sub<Vec2> for Vec2 {
-    type Output = Vec2;
+impl<P: Point> Rope<P> {
+    fn new(len: usize, physics: &Physics<P>) -> Self {
+        Self {
+            knots: vec![P::default(); len],
+            slack: (0..len).map(|knot| physics.slack(knot)).collect(),
+            follow: physics.follow,
+        }
+    }
 
-    fn sub(self, rhs: Vec2) -> Self::Output {
-        Vec2 {
-            x: self.x - rhs.x,
-            y: self.y - rhs.y,
+    /// Moves the head one step and drags every other knot after it.
+    /// Returns whether every knot moved the same way as the head.
+    fn step(&mut self, dir: P) -> bool {
+        self.knots[0] = self.knots[0] + dir;
+        let mut shifted = true;
+        for i in 1..self.knots.len() {
+            let knot = self
+                .follow
+                .follow(self.knots[i - 1], self.knots[i], self.slack[i]);
+            if knot == self.knots[i] {
+                // Nothing behind a knot that stays put moves either.
+                return false;
+            }
+            shifted &= knot == self.knots[i] + dir;
+            self.knots[i] = knot;
         }
+        shifted
     }
+
+    /// Moves the head `count` steps, adding each cell the tail passes
+    /// through to `trail`.
+    ///
+    /// Follow rules only look at where knots are relative to each other, so
+    /// once a step moves the whole rope along with the head, every later
+    /// step of the move does the same. The rest of the move then shifts the
+    /// rope in one go, only tracing the tail's straight line.
+    fn advance(&mut self, dir: P, count: usize, trail: &mut Bitmap<P>) {
+        for done in 1..=count {
+            let shifted = self.step(dir);
+            let mut tail = self.tail();
+            trail.insert(tail);
+            if shifted {
+                let rest = count - done;
+                for _ in 0..rest {
+                    tail = tail + dir;
+                    trail.insert(tail);
+                }
+                let offset = dir.map(|c| c * rest as i32);
+                for knot in &mut self.knots {
+                    *knot = *knot + offset;
+                }
+                return;
+            }
+        }
+    }
+
+    fn tail(&self) -> P {
+        self.knots[self.knots.len() - 1]
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Instruction<P: Point = Vec2> {
+    dir: P,
+    count: usize,
 }
 
-impl std::ops::Add<Vec2> for Vec2 {
-    type Output = Vec2;
+impl<P: Point> std::str::FromStr for Instruction<P> {
+    type Err = ();
 
-    fn add(self, rhs: Vec2) -> Self::Output {
-        Vec2 {
-            x: self.x + rhs.x,
-            y: self.y + rhs.y,
-        }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (direction, count_str) = s.split_once(' ').ok_or(())?;
+        let dir = P::MOVES
+            .iter()
+            .find(|(name, _)| *name == direction)
+            .ok_or(())?
+            .1;
+        Ok(Self {
+            dir,
+            count: count_str.parse().map_err(|_| ())?,
+        })
+    }
+}
+
+impl<P: Point> std::fmt::Display for Instruction<P> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, _) = P::MOVES
+            .iter()
+            .find(|(_, dir)| *dir == self.dir)
+            .ok_or(std::fmt::Error)?;
+        write!(f, "{name} {}", self.count)
     }
 }
@@ -0,0 +1,103 @@
+use std::fmt::Debug;
+
+use crate::point::{Point, Vec2};
+
+/// How a knot moves to keep up with the knot ahead of it. Rules must only
+/// depend on where the knots are relative to each other, as moving the
+/// whole rope along is taken as a shortcut for long moves.
+pub trait FollowRule<P: Point>: Debug {
+    /// Where a knot at `tail` ends up once the knot ahead of it has moved to
+    /// `head`, leaving them no more than `slack` apart.
+    fn follow(&self, head: P, tail: P, slack: i32) -> P;
+}
+
+/// The puzzle's rule. Knots may sit up to `slack` apart in any direction,
+/// including diagonally, and a knot that falls behind takes a single step
+/// towards the one ahead, diagonally if they're in different rows and
+/// columns.
+#[derive(Debug, Clone, Copy)]
+pub struct Chebyshev;
+
+impl<P: Point> FollowRule<P> for Chebyshev {
+    fn follow(&self, head: P, mut tail: P, slack: i32) -> P {
+        loop {
+            let delta = head - tail;
+            if delta.coords().all(|c| c.abs() <= slack) {
+                return tail;
+            }
+            tail = tail + delta.map(|c| c.clamp(-1, 1));
+        }
+    }
+}
+
+/// Knots may only be `slack` steps apart counting along rows and columns,
+/// and a knot that falls behind only moves along them, closing the larger
+/// gap first.
+#[derive(Debug, Clone, Copy)]
+pub struct Manhattan;
+
+impl<P: Point> FollowRule<P> for Manhattan {
+    fn follow(&self, head: P, mut tail: P, slack: i32) -> P {
+        loop {
+            let delta = head - tail;
+            if delta.coords().map(i32::abs).sum::<i32>() <= slack {
+                return tail;
+            }
+            // Step along the first axis with the largest gap.
+            let largest = delta.coords().map(i32::abs).max().unwrap_or(0);
+            let mut stepped = false;
+            tail = tail
+                + delta.map(|c| {
+                    if !stepped && c.abs() == largest {
+                        stepped = true;
+                        c.signum()
+                    } else {
+                        0
+                    }
+                });
+        }
+    }
+}
+
+/// The follow rule and the slack allowed along each segment of the rope.
+#[derive(Debug, Clone)]
+pub struct Physics<P: Point = Vec2> {
+    /// Slack for each segment from the head back, with the last value used
+    /// for any segments past the end.
+    pub slack: Vec<i32>,
+    pub follow: &'static dyn FollowRule<P>,
+}
+
+impl<P: Point> Default for Physics<P> {
+    fn default() -> Self {
+        Self {
+            slack: vec![1],
+            follow: &Chebyshev,
+        }
+    }
+}
+
+impl<P: Point> Physics<P> {
+    /// The slack between knot `knot` and the one ahead of it.
+    pub fn slack(&self, knot: usize) -> i32 {
+        let segment = knot.saturating_sub(1);
+        *self.slack.get(segment).or(self.slack.last()).unwrap_or(&1)
+    }
+}
+
+pub fn parse_slack(s: &str) -> Result<Vec<i32>, ()> {
+    s.split(',')
+        .map(|v| match v.trim().parse() {
+            Ok(slack) if slack >= 0 => Ok(slack),
+            _ => Err(()),
+        })
+        .collect()
+}
+
+pub fn parse_follow<P: Point>(s: &str) -> Result<&'static dyn FollowRule<P>, ()> {
+    match s {
+        "chebyshev" => Ok(&Chebyshev),
+        "manhattan" => Ok(&Manhattan),
+        _ => Err(()),
+    }
+}
@@ -0,0 +1,136 @@
+use std::{
+    fmt::Debug,
+    hash::Hash,
+    ops::{Add, Sub},
+};
+
+/// A cell on a grid of any number of dimensions.
+pub trait Point:
+    Copy + Eq + Hash + Debug + Default + Add<Output = Self> + Sub<Output = Self> + 'static
+{
+    const DIMENSIONS: usize;
+    /// The head's moves by instruction letter.
+    const MOVES: &'static [(&'static str, Self)];
+
+    fn from_fn(f: impl FnMut(usize) -> i32) -> Self;
+
+    fn coord(self, axis: usize) -> i32;
+
+    fn map(self, mut f: impl FnMut(i32) -> i32) -> Self {
+        Self::from_fn(|axis| f(self.coord(axis)))
+    }
+
+    fn coords(self) -> impl Iterator<Item = i32> {
+        (0..Self::DIMENSIONS).map(move |axis| self.coord(axis))
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Vec2 {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Point for Vec2 {
+    const DIMENSIONS: usize = 2;
+    const MOVES: &'static [(&'static str, Self)] = &[
+        ("U", Vec2 { x: 0, y: 1 }),
+        ("D", Vec2 { x: 0, y: -1 }),
+        ("L", Vec2 { x: -1, y: 0 }),
+        ("R", Vec2 { x: 1, y: 0 }),
+        ("UL", Vec2 { x: -1, y: 1 }),
+        ("UR", Vec2 { x: 1, y: 1 }),
+        ("DL", Vec2 { x: -1, y: -1 }),
+        ("DR", Vec2 { x: 1, y: -1 }),
+    ];
+
+    fn from_fn(mut f: impl FnMut(usize) -> i32) -> Self {
+        Vec2 { x: f(0), y: f(1) }
+    }
+
+    fn coord(self, axis: usize) -> i32 {
+        [self.x, self.y][axis]
+    }
+}
+
+impl Sub<Vec2> for Vec2 {
+    type Output = Vec2;
+
+    fn sub(self, rhs: Vec2) -> Self::Output {
+        Vec2 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+        }
+    }
+}
+
+impl Add<Vec2> for Vec2 {
+    type Output = Vec2;
+
+    fn add(self, rhs: Vec2) -> Self::Output {
+        Vec2 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+        }
+    }
+}
+
+/// A 2D grid stacked along `z`, with forward being positive.
+#[derive(Debug, Default, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Vec3 {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+impl Point for Vec3 {
+    const DIMENSIONS: usize = 3;
+    const MOVES: &'static [(&'static str, Self)] = &[
+        ("U", Vec3 { x: 0, y: 1, z: 0 }),
+        ("D", Vec3 { x: 0, y: -1, z: 0 }),
+        ("L", Vec3 { x: -1, y: 0, z: 0 }),
+        ("R", Vec3 { x: 1, y: 0, z: 0 }),
+        ("F", Vec3 { x: 0, y: 0, z: 1 }),
+        ("B", Vec3 { x: 0, y: 0, z: -1 }),
+        ("UL", Vec3 { x: -1, y: 1, z: 0 }),
+        ("UR", Vec3 { x: 1, y: 1, z: 0 }),
+        ("DL", Vec3 { x: -1, y: -1, z: 0 }),
+        ("DR", Vec3 { x: 1, y: -1, z: 0 }),
+    ];
+
+    fn from_fn(mut f: impl FnMut(usize) -> i32) -> Self {
+        Vec3 {
+            x: f(0),
+            y: f(1),
+            z: f(2),
+        }
+    }
+
+    fn coord(self, axis: usize) -> i32 {
+        [self.x, self.y, self.z][axis]
+    }
+}
+
+impl Sub<Vec3> for Vec3 {
+    type Output = Vec3;
+
+    fn sub(self, rhs: Vec3) -> Self::Output {
+        Vec3 {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
+
+impl Add<Vec3> for Vec3 {
+    type Output = Vec3;
+
+    fn add(self, rhs: Vec3) -> Self::Output {
+        Vec3 {
+            x: self.x + rhs.x,
+            y: self.y + rhs.y,
+            z: self.z + rhs.z,
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use crate::{
+    point::{Point, Vec2},
+    Rope,
+};
+
+/// Where every knot of a rope has been.
+#[derive(Debug, Clone)]
+pub struct Visits<P: Point = Vec2> {
+    /// For each knot, head first, how many times it arrived at each cell.
+    /// Starting on a cell counts as arriving there, staying put doesn't.
+    knots: Vec<HashMap<P, usize>>,
+    /// Where each knot was when last recorded.
+    last: Vec<P>,
+}
+
+impl<P: Point> Visits<P> {
+    /// Starts recording from the rope's current position.
+    pub fn new(rope: &Rope<P>) -> Self {
+        Self {
+            knots: rope
+                .knots
+                .iter()
+                .map(|pos| HashMap::from([(*pos, 1)]))
+                .collect(),
+            last: rope.knots.clone(),
+        }
+    }
+
+    /// Records the rope after a step. Knots that moved arrive at a new cell.
+    pub fn record(&mut self, rope: &Rope<P>) {
+        for ((visits, old), new) in self.knots.iter_mut().zip(&mut self.last).zip(&rope.knots) {
+            if old != new {
+                *visits.entry(*new).or_default() += 1;
+                *old = *new;
+            }
+        }
+    }
+
+    /// How many different cells knot `knot` touched.
+    pub fn cells(&self, knot: usize) -> usize {
+        self.knots[knot].len()
+    }
+
+    /// The `n` cells knot `knot` arrived at most often, most visited first
+    /// and then by position.
+    pub fn most_visited(&self, knot: usize, n: usize) -> Vec<(P, usize)> {
+        let mut cells: Vec<(P, usize)> = self.knots[knot]
+            .iter()
+            .map(|(pos, count)| (*pos, *count))
+            .collect();
+        cells.sort_by_cached_key(|(pos, count)| {
+            let mut coords: Vec<i32> = pos.coords().collect();
+            coords.reverse();
+            (std::cmp::Reverse(*count), coords)
+        });
+        cells.truncate(n);
+        cells
+    }
+}
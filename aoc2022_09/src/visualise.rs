@@ -0,0 +1,250 @@
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{gif::GifWriter, physics::Physics, Instruction, Rope, Vec2};
+
+pub struct Options {
+    /// Animate in the terminal.
+    pub play: bool,
+    /// Time each frame stays on screen, in milliseconds.
+    pub delay_ms: u64,
+    /// Only show every nth step. The final state is always shown.
+    pub every: usize,
+    /// Write each frame to a numbered text file in this directory.
+    pub frames_dir: Option<String>,
+    /// Write the animation to this GIF file.
+    pub gif_path: Option<String>,
+    /// Pixels per cell in the GIF.
+    pub scale: usize,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            play: false,
+            delay_ms: 100,
+            every: 1,
+            frames_dir: None,
+            gif_path: None,
+            scale: 4,
+        }
+    }
+}
+
+/// The rope and the tail's trail after one step.
+struct Frame<'a> {
+    label: &'a str,
+    knots: &'a [Vec2],
+    trail: &'a HashSet<Vec2>,
+}
+
+/// Runs the simulation one step at a time, rendering each step as requested.
+pub fn run(
+    instructions: &[Instruction],
+    knots: usize,
+    physics: &Physics,
+    options: &Options,
+) -> io::Result<()> {
+    // A first pass finds the area to draw, so frames are all the same size.
+    let mut grid = Grid::default();
+    let mut steps = 0;
+    simulate(instructions, knots, physics, |frame| {
+        grid.include(frame.knots);
+        steps += 1;
+        Ok(())
+    })?;
+
+    if let Some(dir) = &options.frames_dir {
+        std::fs::create_dir_all(dir)?;
+    }
+    let mut gif = match &options.gif_path {
+        Some(path) => {
+            let scale = options.scale.max(1);
+            let (width, height) = (grid.width() * scale, grid.height() * scale);
+            if width > u16::MAX as usize || height > u16::MAX as usize {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "animation too large for a GIF, try a smaller --scale",
+                ));
+            }
+            let out = BufWriter::new(File::create(path)?);
+            Some(GifWriter::new(out, width as u16, height as u16, &PALETTE)?)
+        }
+        None => None,
+    };
+    let delay_cs = (options.delay_ms / 10).min(u16::MAX as u64) as u16;
+    let mut stdout = io::stdout().lock();
+
+    let every = options.every.max(1);
+    let mut step = 0;
+    let mut shown = 0;
+    simulate(instructions, knots, physics, |frame| {
+        let due = step % every == 0 || step == steps - 1;
+        step += 1;
+        if !due {
+            return Ok(());
+        }
+        if let Some(dir) = &options.frames_dir {
+            let text = format!("{}\n\n{}", frame.label, grid.text(&frame));
+            std::fs::write(Path::new(dir).join(format!("frame_{shown:05}.txt")), text)?;
+        }
+        if let Some(gif) = &mut gif {
+            gif.frame(&grid.pixels(&frame, options.scale.max(1)), delay_cs)?;
+        }
+        if options.play {
+            write!(
+                stdout,
+                "\x1b[2J\x1b[H{}\n\n{}",
+                frame.label,
+                grid.text(&frame)
+            )?;
+            stdout.flush()?;
+            std::thread::sleep(Duration::from_millis(options.delay_ms));
+        }
+        shown += 1;
+        Ok(())
+    })?;
+
+    if let Some(gif) = gif {
+        gif.finish()?;
+    }
+    Ok(())
+}
+
+/// Calls `f` with the initial state and then after every single step.
+fn simulate(
+    instructions: &[Instruction],
+    knots: usize,
+    physics: &Physics,
+    mut f: impl FnMut(Frame) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut rope = Rope::new(knots, physics);
+    let mut trail = HashSet::from([rope.tail()]);
+    f(Frame {
+        label: "== Initial State ==",
+        knots: &rope.knots,
+        trail: &trail,
+    })?;
+
+    for instruction in instructions {
+        let count = instruction.count;
+        for step in 1..=count {
+            rope.step(instruction.dir);
+            trail.insert(rope.tail());
+            f(Frame {
+                label: &format!("== {instruction} ({step}/{count}) =="),
+                knots: &rope.knots,
+                trail: &trail,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// What occupies a cell, drawn in order of precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    Trail,
+    Start,
+    /// A knot, with the head at index 0.
+    Knot(usize),
+}
+
+/// Empty, trail, start, head, then the rest of the knots.
+const PALETTE: [[u8; 3]; 5] = [
+    [24, 24, 32],
+    [70, 110, 200],
+    [120, 200, 120],
+    [240, 80, 60],
+    [240, 220, 200],
+];
+
+/// The fixed area every frame is drawn in, so the view doesn't jump about.
+/// It always includes the start.
+#[derive(Default)]
+struct Grid {
+    min: Vec2,
+    max: Vec2,
+}
+
+impl Grid {
+    fn include(&mut self, knots: &[Vec2]) {
+        for pos in knots {
+            self.min = Vec2 {
+                x: self.min.x.min(pos.x),
+                y: self.min.y.min(pos.y),
+            };
+            self.max = Vec2 {
+                x: self.max.x.max(pos.x),
+                y: self.max.y.max(pos.y),
+            };
+        }
+    }
+
+    fn width(&self) -> usize {
+        (self.max.x - self.min.x + 1) as usize
+    }
+
+    fn height(&self) -> usize {
+        (self.max.y - self.min.y + 1) as usize
+    }
+
+    /// The cells of a frame, top row first as up is positive y.
+    fn cells(&self, frame: &Frame) -> Vec<Cell> {
+        let mut cells = vec![Cell::Empty; self.width() * self.height()];
+        let index = |pos: Vec2| {
+            (self.max.y - pos.y) as usize * self.width() + (pos.x - self.min.x) as usize
+        };
+        for pos in frame.trail {
+            cells[index(*pos)] = Cell::Trail;
+        }
+        cells[index(Vec2 { x: 0, y: 0 })] = Cell::Start;
+        // Earlier knots cover later ones, so draw from the tail forwards.
+        for (i, pos) in frame.knots.iter().enumerate().rev() {
+            cells[index(*pos)] = Cell::Knot(i);
+        }
+        cells
+    }
+
+    fn text(&self, frame: &Frame) -> String {
+        let tail = frame.knots.len() - 1;
+        let mut out = String::new();
+        for row in self.cells(frame).chunks(self.width()) {
+            out.extend(row.iter().map(|cell| match cell {
+                Cell::Empty => '.',
+                Cell::Trail => '#',
+                Cell::Start => 's',
+                Cell::Knot(0) => 'H',
+                Cell::Knot(1) if tail == 1 => 'T',
+                Cell::Knot(i) => char::from_digit(*i as u32, 36).unwrap_or('*'),
+            }));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Palette indices for a frame, `scale` pixels square per cell.
+    fn pixels(&self, frame: &Frame, scale: usize) -> Vec<u8> {
+        let cells = self.cells(frame);
+        let width = self.width() * scale;
+        let mut pixels = Vec::with_capacity(cells.len() * scale * scale);
+        for y in 0..self.height() * scale {
+            for x in 0..width {
+                pixels.push(match cells[(y / scale) * self.width() + x / scale] {
+                    Cell::Empty => 0,
+                    Cell::Trail => 1,
+                    Cell::Start => 2,
+                    Cell::Knot(0) => 3,
+                    Cell::Knot(_) => 4,
+                });
+            }
+        }
+        pixels
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt::{self, Write},
+};
+
+use crate::isa::{Condition, Instruction, Program, Register};
+
+#[derive(Debug)]
+pub struct AsmError {
+    /// One-based line number in the source.
+    pub line: usize,
+    pub kind: ErrorKind,
+}
+
+#[derive(Debug)]
+pub enum ErrorKind {
+    UnknownMnemonic(String),
+    /// Missing, extra or unreadable operands.
+    BadOperands(String),
+    UnknownLabel(String),
+    DuplicateLabel(String),
+    /// A relative jump that lands outside the program.
+    OutOfRange(String),
+}
+
+impl fmt::Display for AsmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: ", self.line)?;
+        match &self.kind {
+            ErrorKind::UnknownMnemonic(m) => write!(f, "unknown instruction `{m}`"),
+            ErrorKind::BadOperands(i) => write!(f, "bad operands in `{i}`"),
+            ErrorKind::UnknownLabel(l) => write!(f, "no label `{l}`"),
+            ErrorKind::DuplicateLabel(l) => write!(f, "label `{l}` defined twice"),
+            ErrorKind::OutOfRange(t) => write!(f, "jump `{t}` leaves the program"),
+        }
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+/// Assembles source with one instruction per line.
+///
+/// A line may start with `label:`, and anything after `;` or `#` is a
+/// comment. Jumps go to a label or to an offset from the jump itself, such
+/// as `jmp -2`. A label after the last instruction marks the end, and
+/// jumping there halts.
+pub fn assemble(source: &str) -> Result<Program, AsmError> {
+    let lines: Vec<(Option<&str>, &str)> = source.lines().map(split_line).collect();
+
+    let mut labels = HashMap::new();
+    let mut pc = 0;
+    for (idx, (label, text)) in lines.iter().enumerate() {
+        if let Some(label) = label {
+            if labels.insert(*label, pc).is_some() {
+                return Err(AsmError {
+                    line: idx + 1,
+                    kind: ErrorKind::DuplicateLabel(label.to_string()),
+                });
+            }
+        }
+        if !text.is_empty() {
+            pc += 1;
+        }
+    }
+    let len = pc;
+
+    let mut program = vec![];
+    for (idx, (_, text)) in lines.iter().enumerate() {
+        if text.is_empty() {
+            continue;
+        }
+        let instruction =
+            parse_instruction(text, program.len(), len, &labels).map_err(|kind| AsmError {
+                line: idx + 1,
+                kind,
+            })?;
+        program.push(instruction);
+    }
+    Ok(program)
+}
+
+/// Splits a line into its label, if any, and the instruction text without
+/// comments or surrounding space.
+fn split_line(line: &str) -> (Option<&str>, &str) {
+    let line = match line.find([';', '#']) {
+        Some(idx) => &line[..idx],
+        None => line,
+    };
+    let line = line.trim();
+    match line.split_once(':') {
+        Some((label, rest)) => (Some(label.trim()), rest.trim()),
+        None => (None, line),
+    }
+}
+
+fn parse_instruction(
+    text: &str,
+    pc: usize,
+    len: usize,
+    labels: &HashMap<&str, usize>,
+) -> Result<Instruction, ErrorKind> {
+    let bad_operands = || ErrorKind::BadOperands(text.to_string());
+    let mut words = text.split_whitespace();
+    let mnemonic = words.next().unwrap_or_default();
+    let operands: Vec<&str> = words.collect();
+
+    let value = |s: &str| s.parse::<i32>().map_err(|_| bad_operands());
+    let register = |s: &str| Register::parse(s).ok_or_else(bad_operands);
+    let target = |s: &str| -> Result<usize, ErrorKind> {
+        if s.starts_with(['+', '-']) {
+            let offset: isize = s.parse().map_err(|_| bad_operands())?;
+            return pc
+                .checked_add_signed(offset)
+                .filter(|target| *target <= len)
+                .ok_or_else(|| ErrorKind::OutOfRange(s.to_string()));
+        }
+        labels
+            .get(s)
+            .copied()
+            .ok_or_else(|| ErrorKind::UnknownLabel(s.to_string()))
+    };
+
+    if let Some(condition) = Condition::ALL.iter().find(|c| c.mnemonic() == mnemonic) {
+        let [reg, to] = operands[..] else {
+            return Err(bad_operands());
+        };
+        return Ok(Instruction::Branch(*condition, register(reg)?, target(to)?));
+    }
+
+    if let Some(reg) = mnemonic.strip_prefix("add").and_then(Register::parse) {
+        let [v] = operands[..] else {
+            return Err(bad_operands());
+        };
+        return Ok(Instruction::Add(reg, value(v)?));
+    }
+    if let Some(reg) = mnemonic.strip_prefix("mul").and_then(Register::parse) {
+        let [v] = operands[..] else {
+            return Err(bad_operands());
+        };
+        return Ok(Instruction::Mul(reg, value(v)?));
+    }
+
+    match (mnemonic, &operands[..]) {
+        ("noop", []) => Ok(Instruction::Noop),
+        ("jmp", [to]) => Ok(Instruction::Jmp(target(to)?)),
+        ("noop" | "jmp", _) => Err(bad_operands()),
+        _ => Err(ErrorKind::UnknownMnemonic(mnemonic.to_string())),
+    }
+}
+
+/// Prints a program back as source that assembles to the same program,
+/// naming each jump target `L` and its index.
+pub fn disassemble(program: &Program) -> String {
+    let targets: BTreeSet<usize> = program.iter().filter_map(|i| i.target()).collect();
+
+    let mut out = String::new();
+    for (pc, instruction) in program.iter().enumerate() {
+        if targets.contains(&pc) {
+            let _ = writeln!(out, "L{pc}:");
+        }
+        let _ = writeln!(out, "    {instruction}");
+    }
+    if targets.contains(&program.len()) {
+        let _ = writeln!(out, "L{}:", program.len());
+    }
+    out
+}
@@ -0,0 +1,385 @@
+use std::{
+    fmt,
+    io::{self, BufRead, Write},
+};
+
+use crate::{
+    isa::{Program, Register},
+    render, Geometry, Machine,
+};
+
+const HELP: &str = "\
+break cycle N | break pc N   stop before cycle N, or before the instruction at N starts
+watch x|y [OP VALUE]         stop when a register changes, or when OP (== != < <= > >=) becomes true
+delete ID                    remove a breakpoint or watch
+info                         list breakpoints and watches
+tick [N]                     run N clock cycles (default 1)
+step [N]                     run N whole instructions (default 1)
+continue                     run until something stops it or the program ends
+print x|y                    show a register
+cpu | crt                    show the state of the CPU or the screen
+list                         show the program around the current instruction
+reset                        start the program again, keeping breakpoints and watches
+quit
+An empty line repeats the last command.";
+
+/// Where execution stops, checked before each clock cycle starts.
+#[derive(Debug, Clone, Copy)]
+enum Breakpoint {
+    /// Before cycle `n` starts, so registers already hold their values
+    /// during it.
+    Cycle(u32),
+    /// Before the instruction at this index starts.
+    Pc(usize),
+}
+
+impl fmt::Display for Breakpoint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cycle(n) => write!(f, "before cycle {n}"),
+            Self::Pc(pc) => write!(f, "at pc {pc}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Compare {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Compare {
+    const ALL: [(&'static str, Self); 6] = [
+        ("==", Self::Eq),
+        ("!=", Self::Ne),
+        ("<", Self::Lt),
+        ("<=", Self::Le),
+        (">", Self::Gt),
+        (">=", Self::Ge),
+    ];
+
+    fn holds(&self, lhs: i32, rhs: i32) -> bool {
+        match self {
+            Self::Eq => lhs == rhs,
+            Self::Ne => lhs != rhs,
+            Self::Lt => lhs < rhs,
+            Self::Le => lhs <= rhs,
+            Self::Gt => lhs > rhs,
+            Self::Ge => lhs >= rhs,
+        }
+    }
+}
+
+/// Stops when a register changes, or when a comparison on it becomes true.
+#[derive(Debug, Clone, Copy)]
+struct Watch {
+    reg: Register,
+    test: Option<(Compare, i32)>,
+}
+
+impl Watch {
+    fn parse(words: &[&str]) -> Option<Self> {
+        match words {
+            [reg] => Some(Self {
+                reg: Register::parse(reg)?,
+                test: None,
+            }),
+            [reg, op, value] => {
+                let (_, cmp) = Compare::ALL.iter().find(|(name, _)| name == op)?;
+                Some(Self {
+                    reg: Register::parse(reg)?,
+                    test: Some((*cmp, value.parse().ok()?)),
+                })
+            }
+            _ => None,
+        }
+    }
+
+    fn triggered(&self, before: i32, after: i32) -> bool {
+        match self.test {
+            None => before != after,
+            Some((cmp, value)) => !cmp.holds(before, value) && cmp.holds(after, value),
+        }
+    }
+}
+
+impl fmt::Display for Watch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.test {
+            None => write!(f, "{} changes", self.reg),
+            Some((cmp, value)) => {
+                let (op, _) = Compare::ALL.iter().find(|(_, c)| *c == cmp).unwrap();
+                write!(f, "{} {op} {value}", self.reg)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Stop {
+    Break(Breakpoint),
+    Watch(Watch),
+}
+
+impl fmt::Display for Stop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Break(breakpoint) => write!(f, "breakpoint {breakpoint}"),
+            Self::Watch(watch) => write!(f, "watch {watch}"),
+        }
+    }
+}
+
+/// How far to run before stopping, unless something stops it sooner.
+#[derive(Debug, Clone, Copy)]
+enum Run {
+    Ticks(u64),
+    Instructions(u64),
+    Continue,
+}
+
+struct Debugger<'a> {
+    program: &'a Program,
+    geometry: Geometry,
+    machine: Machine,
+    /// Breakpoints and watches by id, counting from one. Deleted ones leave
+    /// a gap so the others keep their ids.
+    stops: Vec<Option<Stop>>,
+}
+
+/// Reads debugger commands from `input` until it ends or `quit`.
+pub fn run(
+    program: &Program,
+    geometry: Geometry,
+    input: impl BufRead,
+    mut out: impl Write,
+) -> io::Result<()> {
+    let mut debugger = Debugger {
+        program,
+        geometry,
+        machine: Machine::new(geometry),
+        stops: vec![],
+    };
+    writeln!(
+        out,
+        "{} instructions loaded, `help` for commands",
+        program.len()
+    )?;
+    debugger.show_position(&mut out)?;
+
+    let mut last = String::new();
+    write!(out, "(dbg) ")?;
+    out.flush()?;
+    for line in input.lines() {
+        let line = line?;
+        let command = match line.trim() {
+            "" => last.clone(),
+            command => command.to_string(),
+        };
+        if !debugger.command(&command, &mut out)? {
+            break;
+        }
+        last = command;
+        write!(out, "(dbg) ")?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+impl Debugger<'_> {
+    /// Carries out one command, returning whether to keep going.
+    fn command(&mut self, command: &str, out: &mut impl Write) -> io::Result<bool> {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        let count = |arg: Option<&&str>| match arg {
+            Some(n) => n.parse().ok(),
+            None => Some(1),
+        };
+
+        match words[..] {
+            [] => {}
+            ["help" | "h"] => writeln!(out, "{HELP}")?,
+            ["quit" | "q"] => return Ok(false),
+            ["break" | "b", "cycle", n] => match n.parse() {
+                Ok(n) => self.add_stop(Stop::Break(Breakpoint::Cycle(n)), out)?,
+                Err(_) => writeln!(out, "not a cycle: {n}")?,
+            },
+            ["break" | "b", "pc", n] => match n.parse() {
+                Ok(n) => self.add_stop(Stop::Break(Breakpoint::Pc(n)), out)?,
+                Err(_) => writeln!(out, "not an instruction index: {n}")?,
+            },
+            ["watch" | "w", ref rest @ ..] => match Watch::parse(rest) {
+                Some(watch) => self.add_stop(Stop::Watch(watch), out)?,
+                None => writeln!(out, "usage: watch x|y [OP VALUE]")?,
+            },
+            ["delete" | "d", id] => {
+                let stop = id
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|id| self.stops.get_mut(id.checked_sub(1)?))
+                    .and_then(Option::take);
+                match stop {
+                    Some(stop) => writeln!(out, "deleted {id}: {stop}")?,
+                    None => writeln!(out, "nothing numbered {id}")?,
+                }
+            }
+            ["info" | "i"] => {
+                for (id, stop) in self.stops() {
+                    writeln!(out, "{id}: {stop}")?;
+                }
+            }
+            ["tick" | "t", ref n @ ..] if n.len() <= 1 => match count(n.first()) {
+                Some(n) => self.resume(Run::Ticks(n), out)?,
+                None => writeln!(out, "usage: tick [N]")?,
+            },
+            ["step" | "s", ref n @ ..] if n.len() <= 1 => match count(n.first()) {
+                Some(n) => self.resume(Run::Instructions(n), out)?,
+                None => writeln!(out, "usage: step [N]")?,
+            },
+            ["continue" | "c"] => self.resume(Run::Continue, out)?,
+            ["print" | "p", reg] => match Register::parse(reg) {
+                Some(reg) => writeln!(out, "{reg} = {}", self.machine.cpu.register(reg))?,
+                None => writeln!(out, "no register {reg}")?,
+            },
+            ["cpu"] => {
+                let cpu = &self.machine.cpu;
+                writeln!(
+                    out,
+                    "clock {}  pc {}  x {}  y {}",
+                    cpu.clock, cpu.program_counter, cpu.reg_x, cpu.reg_y
+                )?;
+                self.show_position(out)?;
+            }
+            ["crt"] => {
+                let crt = &self.machine.crt;
+                writeln!(
+                    out,
+                    "{} pixels drawn, beam at row {} column {}, last pixel {:?}",
+                    crt.pixel_idx,
+                    crt.pixel_idx as usize % crt.framebuffer.len() / self.geometry.width,
+                    crt.pixel_idx as usize % self.geometry.width,
+                    crt.current_pixel
+                )?;
+                write!(out, "{}", render::text(crt))?;
+            }
+            ["list" | "l"] => {
+                let pc = self.machine.cpu.program_counter;
+                let from = pc.saturating_sub(3);
+                let to = (pc + 4).min(self.program.len());
+                for (idx, instruction) in self.program[from..to].iter().enumerate() {
+                    let idx = from + idx;
+                    let marker = if idx == pc { "=>" } else { "  " };
+                    writeln!(out, "{marker} {idx:>4}  {instruction}")?;
+                }
+            }
+            ["reset"] => {
+                self.machine = Machine::new(self.geometry);
+                self.show_position(out)?;
+            }
+            _ => writeln!(out, "unknown command `{command}`, try `help`")?,
+        }
+        Ok(true)
+    }
+
+    fn add_stop(&mut self, stop: Stop, out: &mut impl Write) -> io::Result<()> {
+        self.stops.push(Some(stop));
+        writeln!(out, "{}: {stop}", self.stops.len())
+    }
+
+    fn stops(&self) -> impl Iterator<Item = (usize, Stop)> + '_ {
+        self.stops
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, stop)| Some((idx + 1, (*stop)?)))
+    }
+
+    /// Runs clock cycles until `run` is done, a breakpoint or watch stops
+    /// it, or the program ends. A breakpoint at the starting point doesn't
+    /// stop it again.
+    fn resume(&mut self, run: Run, out: &mut impl Write) -> io::Result<()> {
+        let mut remaining = match run {
+            Run::Ticks(n) | Run::Instructions(n) => n,
+            Run::Continue => u64::MAX,
+        };
+        let mut started = false;
+        loop {
+            if self.machine.halted(self.program) {
+                return writeln!(
+                    out,
+                    "program finished after {} cycles",
+                    self.machine.cpu.clock
+                );
+            }
+            if started {
+                if let Some((id, stop)) = self.stops().find(|(_, stop)| self.hit(stop)) {
+                    writeln!(out, "stopped at {id}: {stop}")?;
+                    return self.show_position(out);
+                }
+            }
+            if remaining == 0 {
+                return self.show_position(out);
+            }
+            started = true;
+
+            let before = self.machine.cpu.clone();
+            self.machine.tick(self.program, |_| {});
+
+            let mut watched = false;
+            for (id, stop) in self.stops() {
+                let Stop::Watch(watch) = stop else {
+                    continue;
+                };
+                let before = before.register(watch.reg);
+                let after = self.machine.cpu.register(watch.reg);
+                if watch.triggered(before, after) {
+                    writeln!(out, "stopped at {id}: {stop}, {before} -> {after}")?;
+                    watched = true;
+                }
+            }
+            if watched {
+                return self.show_position(out);
+            }
+
+            match run {
+                Run::Ticks(_) => remaining -= 1,
+                Run::Instructions(_) if self.machine.cpu.delay_clocks.is_none() => remaining -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    fn hit(&self, stop: &Stop) -> bool {
+        let cpu = &self.machine.cpu;
+        match stop {
+            Stop::Break(Breakpoint::Cycle(n)) => cpu.clock + 1 == *n,
+            Stop::Break(Breakpoint::Pc(pc)) => {
+                cpu.delay_clocks.is_none() && cpu.program_counter == *pc
+            }
+            Stop::Watch(_) => false,
+        }
+    }
+
+    /// The next cycle and the instruction it belongs to.
+    fn show_position(&self, out: &mut impl Write) -> io::Result<()> {
+        let cpu = &self.machine.cpu;
+        let Some(instruction) = self.program.get(cpu.program_counter) else {
+            return writeln!(out, "cycle {}: halted", cpu.clock + 1);
+        };
+        let tick = match cpu.delay_clocks {
+            Some(remaining) => instruction.cycles() - remaining,
+            None => 1,
+        };
+        writeln!(
+            out,
+            "cycle {}: pc {} `{instruction}` tick {tick}/{}  x {}  y {}",
+            cpu.clock + 1,
+            cpu.program_counter,
+            instruction.cycles(),
+            cpu.reg_x,
+            cpu.reg_y
+        )
+    }
+}
@@ -0,0 +1,110 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Register {
+    X,
+    Y,
+}
+
+impl Register {
+    /// Registers are named by a single lowercase letter, which is also the
+    /// suffix of the mnemonics that write to them.
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "x" => Some(Self::X),
+            "y" => Some(Self::Y),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::X => write!(f, "x"),
+            Self::Y => write!(f, "y"),
+        }
+    }
+}
+
+/// When a branch is taken, by the value of the register it tests.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Condition {
+    Zero,
+    NonZero,
+    Positive,
+    Negative,
+}
+
+impl Condition {
+    pub const ALL: [Self; 4] = [Self::Zero, Self::NonZero, Self::Positive, Self::Negative];
+
+    pub fn holds(&self, value: i32) -> bool {
+        match self {
+            Self::Zero => value == 0,
+            Self::NonZero => value != 0,
+            Self::Positive => value > 0,
+            Self::Negative => value < 0,
+        }
+    }
+
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Self::Zero => "jz",
+            Self::NonZero => "jnz",
+            Self::Positive => "jgz",
+            Self::Negative => "jlz",
+        }
+    }
+}
+
+/// Jump targets are instruction indices. Jumping to the end of the program
+/// halts it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Noop,
+    /// Adds a value to a register, as `addx 3` or `addy -1`.
+    Add(Register, i32),
+    /// Multiplies a register by a value, as `mulx 2`.
+    Mul(Register, i32),
+    Jmp(usize),
+    /// Jumps if the register meets the condition, as `jnz y loop`, and
+    /// carries on otherwise.
+    Branch(Condition, Register, usize),
+}
+
+impl Instruction {
+    /// How many ticks the instruction takes. Its effect happens at the end
+    /// of the last one.
+    pub fn cycles(&self) -> u32 {
+        match self {
+            Self::Noop | Self::Jmp(_) | Self::Branch(..) => 1,
+            Self::Add(..) | Self::Mul(..) => 2,
+        }
+    }
+
+    pub fn target(&self) -> Option<usize> {
+        match self {
+            Self::Jmp(target) | Self::Branch(_, _, target) => Some(*target),
+            _ => None,
+        }
+    }
+}
+
+/// The instruction as assembly source, with jump targets as `L` and the
+/// index.
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Noop => write!(f, "noop"),
+            Self::Add(reg, v) => write!(f, "add{reg} {v}"),
+            Self::Mul(reg, v) => write!(f, "mul{reg} {v}"),
+            Self::Jmp(to) => write!(f, "jmp L{to}"),
+            Self::Branch(condition, reg, to) => {
+                write!(f, "{} {reg} L{to}", condition.mnemonic())
+            }
+        }
+    }
+}
+
+pub type Program = Vec<Instruction>;
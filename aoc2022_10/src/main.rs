@@ -1,14 +1,76 @@
+mod asm;
+mod debugger;
+mod isa;
+mod ocr;
+mod render;
+
+use isa::{Instruction, Program, Register};
+
 const INPUT: &str = include_str!("real_input.txt");
 
 fn main() {
-    let instructions: Program = INPUT.lines().map(|s| s.parse().unwrap()).collect();
+    let mut disassemble = false;
+    let mut debug = false;
+    let mut geometry = Geometry::default();
+    let mut text_path = None;
+    let mut pbm_path = None;
+    let mut png_path = None;
+    let mut scale = 8;
+    // Programs can loop forever, so part 2 gives up after this many cycles.
+    let mut max_cycles = 1_000_000;
+    let mut path = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--disassemble" => disassemble = true,
+            "--debug" => debug = true,
+            "--width" => geometry.width = args.next().unwrap().parse().unwrap(),
+            "--height" => geometry.height = args.next().unwrap().parse().unwrap(),
+            "--sprite" => geometry.sprite_width = args.next().unwrap().parse().unwrap(),
+            "--text" => text_path = args.next(),
+            "--pbm" => pbm_path = args.next(),
+            "--png" => png_path = args.next(),
+            "--scale" => scale = args.next().unwrap().parse().unwrap(),
+            "--max-cycles" => max_cycles = args.next().unwrap().parse().unwrap(),
+            _ => path = Some(arg),
+        }
+    }
+    assert!(
+        geometry.width > 0 && geometry.height > 0,
+        "The screen needs at least one pixel"
+    );
+
+    // An optional path assembles a program at runtime instead.
+    let input = match path {
+        Some(path) => std::fs::read_to_string(path).unwrap(),
+        None => INPUT.to_string(),
+    };
+    let instructions = match asm::assemble(&input) {
+        Ok(program) => program,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    if disassemble {
+        print!("{}", asm::disassemble(&instructions));
+        return;
+    }
+    if debug {
+        let stdin = std::io::stdin().lock();
+        debugger::run(&instructions, geometry, stdin, std::io::stdout().lock()).unwrap();
+        return;
+    }
 
     // part 1
     {
+        // Nothing after cycle 220 counts, so there's no need to run further.
         let mut part1 = 0;
-        Machine::default().execute(&instructions, |machine| {
-            if machine.cpu.clock < 221 && (machine.cpu.clock as i64 - 20) % 40 == 0 {
-                part1 += machine.cpu.clock as i32 * machine.cpu.reg_x;
+        Machine::default().execute(&instructions, 220, |machine| {
+            if (machine.cpu.clock as i64 - 20) % 40 == 0 {
+                part1 += machine.cpu.clock as i64 * machine.cpu.reg_x as i64;
             }
         });
         dbg!(part1);
@@ -16,13 +78,25 @@ fn main() {
 
     // part 2
     {
-        println!("part 2");
-        Machine::default().execute(&instructions, |machine| {
-            print!("{}", machine.crt.current_pixel);
-            if machine.crt.is_eol() {
-                println!("");
-            }
-        });
+        let mut machine = Machine::new(geometry);
+        if !machine.execute(&instructions, max_cycles, |_| {}) {
+            eprintln!("program still running after {max_cycles} cycles, showing the screen so far");
+        }
+        let crt = &machine.crt;
+
+        print!("{}", render::text(crt));
+        if let Some(path) = text_path {
+            std::fs::write(path, render::text(crt)).unwrap();
+        }
+        if let Some(path) = pbm_path {
+            std::fs::write(path, render::pbm(crt)).unwrap();
+        }
+        if let Some(path) = png_path {
+            std::fs::write(path, render::png(crt, scale)).unwrap();
+        }
+
+        let part2 = ocr::read(&crt.framebuffer, geometry.width);
+        dbg!(part2);
     }
 }
 
@@ -33,20 +107,47 @@ struct Machine {
 }
 
 impl Machine {
-    fn execute<F: FnMut(&Machine)>(&mut self, program: &Program, mut observer: F) {
-        // self.cpu.execute(program, |cpu| {})
-        while self.cpu.program_counter < program.len() {
-            self.cpu.begin_tick();
-            self.crt.tick(&self.cpu);
-            observer(self);
-            self.cpu.end_tick(program);
+    fn new(geometry: Geometry) -> Self {
+        Self {
+            cpu: Cpu::default(),
+            crt: Crt::new(geometry),
+        }
+    }
+
+    /// Runs until the program ends or the clock reaches `max_cycles`,
+    /// returning whether it ended.
+    fn execute<F: FnMut(&Machine)>(
+        &mut self,
+        program: &Program,
+        max_cycles: u32,
+        mut observer: F,
+    ) -> bool {
+        while !self.halted(program) {
+            if self.cpu.clock >= max_cycles {
+                return false;
+            }
+            self.tick(program, &mut observer);
         }
+        true
+    }
+
+    /// Runs a single clock cycle, calling `observer` during it.
+    fn tick<F: FnMut(&Machine)>(&mut self, program: &Program, mut observer: F) {
+        self.cpu.begin_tick();
+        self.crt.tick(&self.cpu);
+        observer(self);
+        self.cpu.end_tick(program);
+    }
+
+    fn halted(&self, program: &Program) -> bool {
+        self.cpu.program_counter >= program.len()
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 struct Cpu {
     reg_x: i32,
+    reg_y: i32,
     clock: u32,
 
     program_counter: usize,
@@ -57,6 +158,7 @@ impl Default for Cpu {
     fn default() -> Self {
         Self {
             reg_x: 1,
+            reg_y: 0,
             clock: 0,
             program_counter: 0,
             delay_clocks: None,
@@ -69,66 +171,118 @@ impl Cpu {
         self.clock += 1;
     }
 
+    /// Finishes the current instruction once all its ticks have passed.
     fn end_tick(&mut self, program: &Program) {
-        match (&program[self.program_counter], self.delay_clocks) {
-            (Instruction::Addx(_), None) => {
-                self.delay_clocks = Some(0);
-            }
-            (Instruction::Addx(v), Some(0)) => {
-                self.reg_x += v;
-                self.program_counter += 1;
-                self.delay_clocks = None;
+        let instruction = program[self.program_counter];
+        let remaining = match self.delay_clocks {
+            Some(d) => d,
+            None => instruction.cycles() - 1,
+        };
+        if remaining > 0 {
+            self.delay_clocks = Some(remaining - 1);
+            return;
+        }
+
+        self.delay_clocks = None;
+        self.program_counter = match instruction {
+            Instruction::Noop => self.program_counter + 1,
+            // Registers wrap around like the hardware's would.
+            Instruction::Add(reg, v) => {
+                let value = self.register_mut(reg);
+                *value = value.wrapping_add(v);
+                self.program_counter + 1
             }
-            (Instruction::Addx(_), Some(d)) => {
-                self.delay_clocks = Some(d - 1);
+            Instruction::Mul(reg, v) => {
+                let value = self.register_mut(reg);
+                *value = value.wrapping_mul(v);
+                self.program_counter + 1
             }
-            (Instruction::Noop, _) => {
-                self.program_counter += 1;
+            Instruction::Jmp(target) => target,
+            Instruction::Branch(condition, reg, target) => {
+                if condition.holds(self.register(reg)) {
+                    target
+                } else {
+                    self.program_counter + 1
+                }
             }
+        };
+    }
+
+    fn register(&self, reg: Register) -> i32 {
+        match reg {
+            Register::X => self.reg_x,
+            Register::Y => self.reg_y,
+        }
+    }
+
+    fn register_mut(&mut self, reg: Register) -> &mut i32 {
+        match reg {
+            Register::X => &mut self.reg_x,
+            Register::Y => &mut self.reg_y,
         }
     }
 }
 
-#[derive(Debug, Default)]
+/// The shape of the screen and of the sprite drawn on it.
+#[derive(Debug, Clone, Copy)]
+struct Geometry {
+    width: usize,
+    height: usize,
+    /// Pixels covered by the sprite, centred on `reg_x`. An even width
+    /// reaches one further to the right.
+    sprite_width: usize,
+}
+
+impl Default for Geometry {
+    fn default() -> Self {
+        Self {
+            width: 40,
+            height: 6,
+            sprite_width: 3,
+        }
+    }
+}
+
+#[derive(Debug)]
 struct Crt {
+    geometry: Geometry,
     pixel_idx: u32,
     current_pixel: char,
+    /// The whole screen in row-major order, lit pixels `true`. Once the
+    /// beam reaches the end it starts over at the top, drawing over the
+    /// last frame.
+    framebuffer: Vec<bool>,
+}
+
+impl Default for Crt {
+    fn default() -> Self {
+        Self::new(Geometry::default())
+    }
 }
 
 impl Crt {
-    fn tick(&mut self, cpu: &Cpu) {
-        let pixel_x = (self.pixel_idx % 40) as i32;
-        if (pixel_x - cpu.reg_x).abs() <= 1 {
-            self.current_pixel = '#'
-        } else {
-            self.current_pixel = '.'
+    fn new(geometry: Geometry) -> Self {
+        Self {
+            geometry,
+            pixel_idx: 0,
+            current_pixel: '.',
+            framebuffer: vec![false; geometry.width * geometry.height],
         }
-        self.pixel_idx += 1;
     }
 
-    fn is_eol(&self) -> bool {
-        self.pixel_idx % 40 == 0
+    fn tick(&mut self, cpu: &Cpu) {
+        let width = self.geometry.width;
+        let pixel_x = (self.pixel_idx as usize % width) as i64;
+        let sprite_width = self.geometry.sprite_width as i64;
+        let sprite_left = cpu.reg_x as i64 - (sprite_width - 1) / 2;
+        let lit = (sprite_left..sprite_left + sprite_width).contains(&pixel_x);
+        self.current_pixel = if lit { '#' } else { '.' };
+        let len = self.framebuffer.len();
+        self.framebuffer[self.pixel_idx as usize % len] = lit;
+        self.pixel_idx += 1;
     }
-}
-
-enum Instruction {
-    Noop,
-    Addx(i32),
-}
-
-impl std::str::FromStr for Instruction {
-    type Err = ();
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match &s[0..4] {
-            "noop" => Ok(Self::Noop),
-            "addx" => {
-                let operand: i32 = s[5..].parse().map_err(|_| ())?;
-                Ok(Self::Addx(operand))
-            }
-            _ => Err(()),
-        }
+    fn rows(&self) -> impl Iterator<Item = &[bool]> {
+        self.framebuffer.chunks(self.geometry.width)
     }
 }
-
-type Program = Vec<Instruction>;
@@ -0,0 +1,54 @@
+/// The letters AoC draws, each four pixels wide and six tall. Letters are
+/// spaced five pixels apart.
+const FONT: [(char, [&str; 6]); 17] = [
+    ('A', [".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+    ('B', ["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+    ('C', [".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+    ('E', ["####", "#...", "###.", "#...", "#...", "####"]),
+    ('F', ["####", "#...", "###.", "#...", "#...", "#..."]),
+    ('G', [".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+    ('H', ["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+    ('I', [".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+    ('J', ["..##", "...#", "...#", "...#", "#..#", ".##."]),
+    ('K', ["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+    ('L', ["#...", "#...", "#...", "#...", "#...", "####"]),
+    ('O', [".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('P', ["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+    ('R', ["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+    ('S', [".###", "#...", "#...", ".##.", "...#", "###."]),
+    ('U', ["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+    ('Z', ["####", "...#", "..#.", ".#..", "#...", "####"]),
+];
+
+const GLYPH_WIDTH: usize = 4;
+const GLYPH_HEIGHT: usize = 6;
+const SPACING: usize = 5;
+
+/// Reads the letters drawn across the top of a screen `width` pixels wide,
+/// with lit pixels `true`. Anything that isn't a known letter reads as `?`.
+pub fn read(pixels: &[bool], width: usize) -> String {
+    if pixels.len() < width * GLYPH_HEIGHT {
+        return String::new();
+    }
+
+    (0..width / SPACING)
+        .map(|n| {
+            let left = n * SPACING;
+            let lit = |x: usize, y: usize| pixels[y * width + left + x];
+            let glyph = FONT.iter().find(|(_, rows)| {
+                rows.iter().enumerate().all(|(y, row)| {
+                    row.bytes()
+                        .enumerate()
+                        .all(|(x, b)| lit(x, y) == (b == b'#'))
+                })
+            });
+            match glyph {
+                Some((letter, _)) => *letter,
+                None if (0..GLYPH_HEIGHT).all(|y| (0..GLYPH_WIDTH).all(|x| !lit(x, y))) => ' ',
+                None => '?',
+            }
+        })
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
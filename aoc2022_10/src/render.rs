@@ -0,0 +1,48 @@
+use crate::Crt;
+
+/// One character per pixel: `#` if lit, `.` if not.
+pub fn text(crt: &Crt) -> String {
+    let mut out = String::new();
+    for row in crt.rows() {
+        out.extend(row.iter().map(|lit| if *lit { '#' } else { '.' }));
+        out.push('\n');
+    }
+    out
+}
+
+/// A binary PBM image with a pixel per CRT pixel. PBM marks lit pixels
+/// with 1, which shows as black.
+pub fn pbm(crt: &Crt) -> Vec<u8> {
+    let (width, height) = (crt.geometry.width, crt.geometry.height);
+    let mut out = format!("P4\n{width} {height}\n").into_bytes();
+    for row in crt.rows() {
+        // Each row is padded out to whole bytes, most significant bit first.
+        for byte in row.chunks(8) {
+            let bits = byte
+                .iter()
+                .enumerate()
+                .fold(0u8, |bits, (i, lit)| bits | (*lit as u8) << (7 - i));
+            out.push(bits);
+        }
+    }
+    out
+}
+
+/// A greyscale PNG image with lit pixels white, `scale` pixels square per
+/// CRT pixel.
+pub fn png(crt: &Crt, scale: usize) -> Vec<u8> {
+    let scale = scale.max(1);
+    let (width, height) = (crt.geometry.width * scale, crt.geometry.height * scale);
+
+    let mut pixels = Vec::with_capacity(width * height);
+    for row in crt.rows() {
+        let mut scanline = vec![];
+        for lit in row {
+            scanline.extend(std::iter::repeat_n(if *lit { 0xff } else { 0x10 }, scale));
+        }
+        for _ in 0..scale {
+            pixels.extend_from_slice(&scanline);
+        }
+    }
+    aoc2022_png::grey(width, height, &pixels)
+}
@@ -0,0 +1,96 @@
+//! Just enough PNG to write uncompressed 8-bit images, shared by the days
+//! that export them.
+
+/// A greyscale image from one byte per pixel, in row-major order.
+pub fn grey(width: usize, height: usize, pixels: &[u8]) -> Vec<u8> {
+    encode(width, height, 0, 1, pixels)
+}
+
+/// A truecolour image from red, green and blue bytes per pixel, in
+/// row-major order.
+pub fn rgb(width: usize, height: usize, pixels: &[u8]) -> Vec<u8> {
+    encode(width, height, 2, 3, pixels)
+}
+
+fn encode(width: usize, height: usize, colour_type: u8, channels: usize, pixels: &[u8]) -> Vec<u8> {
+    assert_eq!(
+        pixels.len(),
+        width * height * channels,
+        "pixels don't match the image size"
+    );
+
+    // Each scanline is prefixed with filter type 0 (none).
+    let mut raw = Vec::with_capacity(pixels.len() + height);
+    for row in pixels.chunks((width * channels).max(1)) {
+        raw.push(0);
+        raw.extend_from_slice(row);
+    }
+
+    let mut ihdr = vec![];
+    ihdr.extend((width as u32).to_be_bytes());
+    ihdr.extend((height as u32).to_be_bytes());
+    // 8-bit depth, then default compression, filter and interlace.
+    ihdr.extend([8, colour_type, 0, 0, 0]);
+
+    let mut out = b"\x89PNG\r\n\x1a\n".to_vec();
+    png_chunk(&mut out, b"IHDR", &ihdr);
+    png_chunk(&mut out, b"IDAT", &zlib_stored(&raw));
+    png_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+fn png_chunk(out: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    out.extend((data.len() as u32).to_be_bytes());
+    let start = out.len();
+    out.extend_from_slice(kind);
+    out.extend_from_slice(data);
+    let crc = crc32(&out[start..]);
+    out.extend(crc.to_be_bytes());
+}
+
+/// Wraps `data` in a zlib stream of uncompressed deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut out = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        out.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        let last = blocks.peek().is_none();
+        let len = block.len() as u16;
+        out.push(last as u8);
+        out.extend(len.to_le_bytes());
+        out.extend((!len).to_le_bytes());
+        out.extend_from_slice(block);
+    }
+    out.extend(adler32(data).to_be_bytes());
+    out
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for b in data {
+        crc ^= *b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    let (mut a, mut b) = (1u32, 0u32);
+    for chunk in data.chunks(5552) {
+        for byte in chunk {
+            a += *byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}